@@ -0,0 +1,358 @@
+#![warn(clippy::nursery, clippy::unwrap_used, clippy::style)]
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    fs,
+    io::{self, BufWriter, Cursor, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use open_mulle::{
+    parsers::{
+        database_language::{try_get_mulledb, MulleDB},
+        director::{
+            bitmap,
+            ink::Transparency,
+            lingo::LingoContext,
+            palette::{self, MacromediaPalette},
+            shape,
+            sound::MacromediaSound,
+            text, DirectorFile, MacromediaCastBitmapMetadata, MacromediaCastMember,
+        },
+    },
+    systems::mulle_asset_helper::{read_palette_member, read_sound_member},
+};
+
+// Lists what is in director files without running the game, and can write the members out as ordinary files
+//   mulle-dump assets/02.dxr                 the chunks and cast members with their number, name, type and size
+//   mulle-dump assets/02.dxr --export dump   also writes every member it can decode to dump/02.dxr/
+// Bitmaps, shapes and buttons become PNG, texts UTF-8, sounds WAV, databases JSON and scripts their disassembly
+
+type DumpResult = Result<(), Box<dyn Error>>;
+
+fn main() -> ExitCode {
+    let mut files = Vec::new();
+    let mut export = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--export" => match args.next() {
+                Some(dir) => export = Some(PathBuf::from(dir)),
+                None => return usage(),
+            },
+            "--help" | "-h" => return usage(),
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+    if files.is_empty() {
+        return usage();
+    }
+
+    let mut failed = false;
+    for file in &files {
+        if let Err(error) = dump(file, export.as_deref()) {
+            eprintln!("{}: {error}", file.display());
+            failed = true;
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: mulle-dump <file.dxr|file.cxt>... [--export <dir>]");
+    ExitCode::FAILURE
+}
+
+fn dump(path: &Path, export: Option<&Path>) -> DumpResult {
+    let name = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().to_lowercase());
+    let mut director_file = DirectorFile::open(&name, Cursor::new(fs::read(path)?))?;
+
+    println!("{name}");
+    println!("  slot  type     length");
+    for (slot, sub_file) in director_file.sub_files.iter().enumerate() {
+        let fourcc = sub_file.fourcc();
+        if fourcc != "free" && fourcc != "junk" {
+            println!("{slot:>6}  {fourcc}  {:>9}", sub_file.entry_length);
+        }
+    }
+
+    for error in &director_file.member_errors {
+        eprintln!("skipping cast member: {error}");
+    }
+    let members = std::mem::take(&mut director_file.members);
+    println!("number  type          size  name");
+    for member in &members {
+        println!(
+            "{:>6}  {:<10} {:>7}  {}",
+            member.number,
+            cast_type_name(member.cast_type),
+            member_size(&director_file, member),
+            member.name.as_deref().unwrap_or_default()
+        );
+    }
+
+    if let Some(export) = export {
+        let out = export.join(&name);
+        fs::create_dir_all(&out)?;
+        export_members(&mut director_file, &members, &out);
+        println!("exported to {}", out.display());
+    }
+    Ok(())
+}
+
+const fn cast_type_name(cast_type: u32) -> &'static str {
+    match cast_type {
+        1 => "bitmap",
+        2 => "filmloop",
+        3 => "text",
+        4 => "palette",
+        5 => "picture",
+        6 => "sound",
+        7 => "button",
+        8 => "shape",
+        9 => "movie",
+        10 => "video",
+        11 => "script",
+        12 => "rich text",
+        13 => "ole",
+        14 => "transition",
+        _ => "unknown",
+    }
+}
+
+/// The bytes of the member itself and of every chunk it links
+fn member_size(
+    director_file: &DirectorFile<Cursor<Vec<u8>>>,
+    member: &MacromediaCastMember,
+) -> u64 {
+    let linked: u64 = director_file
+        .linked_chunks(member)
+        .iter()
+        .filter_map(|(_, slot)| director_file.sub_files.get(*slot as usize))
+        .map(|sub_file| u64::from(sub_file.entry_length))
+        .sum();
+    member.specific_data.len() as u64 + linked
+}
+
+fn export_members(
+    director_file: &mut DirectorFile<Cursor<Vec<u8>>>,
+    members: &[MacromediaCastMember],
+    out: &Path,
+) {
+    // palettes are needed before the bitmaps that use them
+    let mut palettes = HashMap::new();
+    for member in members.iter().filter(|member| member.cast_type == 4) {
+        match read_palette_member(director_file, member) {
+            Ok(Some(palette)) => {
+                palettes.insert(member.number, palette);
+            }
+            Ok(None) => {}
+            Err(error) => eprintln!("skipping palette {}: {error}", member.number),
+        }
+    }
+    let lingo = director_file.lingo().unwrap_or_else(|error| {
+        eprintln!("skipping scripts: {error}");
+        None
+    });
+
+    for member in members {
+        let path = out.join(file_name(member));
+        let exported = match member.cast_type {
+            1 => export_bitmap(director_file, member, &palettes, &path),
+            3 | 12 => export_text(director_file, member, &path),
+            6 => export_sound(director_file, member, &path),
+            7 => export_button(director_file, member, &path),
+            8 => export_shape(director_file, member, &palettes, &path),
+            _ => Ok(()),
+        };
+        if let Err(error) = exported {
+            eprintln!("skipping member {}: {error}", member.number);
+        }
+        if let Some(lingo) = &lingo {
+            if let Err(error) = export_script(lingo, member, &path) {
+                eprintln!("skipping script of member {}: {error}", member.number);
+            }
+        }
+    }
+}
+
+/// The member number and name, without anything a file system could trip over
+fn file_name(member: &MacromediaCastMember) -> String {
+    let Some(name) = &member.name else {
+        return member.number.to_string();
+    };
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{} {name}", member.number)
+}
+
+// only the file itself is read, palettes in the other casts of a movie fall back to its default palette
+fn own_palette(
+    palettes: &HashMap<u32, MacromediaPalette>,
+    cast_lib: i16,
+    number: u32,
+) -> Option<&MacromediaPalette> {
+    if cast_lib > 1 {
+        return None;
+    }
+    palettes.get(&number)
+}
+
+fn linked_chunk(
+    director_file: &mut DirectorFile<Cursor<Vec<u8>>>,
+    member: &MacromediaCastMember,
+    fourcc: &str,
+) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    let Some((_, slot)) = director_file
+        .linked_chunks(member)
+        .into_iter()
+        .find(|(linked_type, _)| linked_type == fourcc)
+    else {
+        return Ok(None);
+    };
+    Ok(Some(director_file.read_chunk(slot)?))
+}
+
+fn export_bitmap(
+    director_file: &mut DirectorFile<Cursor<Vec<u8>>>,
+    member: &MacromediaCastMember,
+    palettes: &HashMap<u32, MacromediaPalette>,
+    path: &Path,
+) -> DumpResult {
+    let metadata = MacromediaCastBitmapMetadata::read(&member.specific_data)?;
+    let Some(bitd) = linked_chunk(director_file, member, "BITD")? else {
+        return Ok(());
+    };
+    let palette = metadata.palette.resolve(
+        metadata.bit_depth(),
+        director_file.config.as_ref(),
+        |cast_lib, number| own_palette(palettes, cast_lib, number),
+    );
+    // the game decides what is transparent from the ink a member is drawn with, the export keeps every pixel
+    let rgba = bitmap::decode_bitd(&metadata, &bitd, palette, Transparency::Opaque);
+    write_png(path, metadata.image_width, metadata.image_height, rgba)
+}
+
+fn export_shape(
+    director_file: &DirectorFile<Cursor<Vec<u8>>>,
+    member: &MacromediaCastMember,
+    palettes: &HashMap<u32, MacromediaPalette>,
+    path: &Path,
+) -> DumpResult {
+    let shape = shape::read_shape(&member.specific_data)?;
+    let palette = palette::default_palette(director_file.config.as_ref(), |cast_lib, number| {
+        own_palette(palettes, cast_lib, number)
+    });
+    write_png(path, shape.width, shape.height, shape.rasterize(palette))
+}
+
+fn export_button(
+    director_file: &mut DirectorFile<Cursor<Vec<u8>>>,
+    member: &MacromediaCastMember,
+    path: &Path,
+) -> DumpResult {
+    let button = shape::read_button(&member.specific_data)?;
+    write_png(path, button.width, button.height, button.rasterize())?;
+    export_text(director_file, member, path)
+}
+
+fn write_png(path: &Path, width: i16, height: i16, rgba: Vec<u8>) -> DumpResult {
+    let image = image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+        .ok_or_else(|| format!("the pixels do not fit {width}x{height}"))?;
+    image.save(path.with_extension("png"))?;
+    Ok(())
+}
+
+fn export_text(
+    director_file: &mut DirectorFile<Cursor<Vec<u8>>>,
+    member: &MacromediaCastMember,
+    path: &Path,
+) -> DumpResult {
+    let text = match linked_chunk(director_file, member, "STXT")? {
+        Some(stxt) => text::read_stxt(&stxt)?,
+        None => match linked_chunk(director_file, member, "RTE1")? {
+            Some(rte1) => text::read_rte1(&rte1),
+            None => return Ok(()),
+        },
+    };
+
+    // the databases of the game are texts too, those also get their parsed form
+    if member
+        .name
+        .as_ref()
+        .is_some_and(|name| name.ends_with("DB"))
+    {
+        let json = match try_get_mulledb(text.text.clone()) {
+            Some(MulleDB::MapData(map)) => serde_json::to_string_pretty(&map)?,
+            Some(MulleDB::PartDB(part)) => serde_json::to_string_pretty(&part)?,
+            None => return Err("the database does not parse".into()),
+        };
+        fs::write(path.with_extension("json"), json)?;
+    }
+
+    // director ends its lines with \r
+    fs::write(path.with_extension("txt"), text.text.replace('\r', "\n"))?;
+    Ok(())
+}
+
+fn export_sound(
+    director_file: &mut DirectorFile<Cursor<Vec<u8>>>,
+    member: &MacromediaCastMember,
+    path: &Path,
+) -> DumpResult {
+    if let Some((sound, _)) = read_sound_member(director_file, member)? {
+        write_wav(&path.with_extension("wav"), &sound)?;
+    }
+    Ok(())
+}
+
+/// 16 bit PCM, the samples are already interleaved like a WAV wants them
+fn write_wav(path: &Path, sound: &MacromediaSound) -> io::Result<()> {
+    let data_length = sound.samples.len() as u32 * 2;
+    let block_align = sound.channels * 2;
+    let mut wav = BufWriter::new(fs::File::create(path)?);
+    wav.write_all(b"RIFF")?;
+    wav.write_u32::<LittleEndian>(36 + data_length)?;
+    wav.write_all(b"WAVE")?;
+    wav.write_all(b"fmt ")?;
+    wav.write_u32::<LittleEndian>(16)?;
+    wav.write_u16::<LittleEndian>(1)?; // PCM
+    wav.write_u16::<LittleEndian>(sound.channels)?;
+    wav.write_u32::<LittleEndian>(sound.sample_rate)?;
+    wav.write_u32::<LittleEndian>(sound.sample_rate * u32::from(block_align))?;
+    wav.write_u16::<LittleEndian>(block_align)?;
+    wav.write_u16::<LittleEndian>(16)?;
+    wav.write_all(b"data")?;
+    wav.write_u32::<LittleEndian>(data_length)?;
+    for sample in &sound.samples {
+        wav.write_i16::<LittleEndian>(*sample)?;
+    }
+    wav.flush()
+}
+
+fn export_script(lingo: &LingoContext, member: &MacromediaCastMember, path: &Path) -> DumpResult {
+    if member.script_id == 0 {
+        return Ok(());
+    }
+    if let Some(script) = lingo.scripts.get(&member.script_id) {
+        fs::write(path.with_extension("lingo.txt"), lingo.disassemble(script))?;
+    }
+    Ok(())
+}
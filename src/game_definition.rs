@@ -0,0 +1,90 @@
+use std::{collections::HashMap, fs};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{launch::LaunchSettings, parsers::director::ink::Transparency, GameState};
+
+// Every Mulle Meck game runs on the same engine with other files, a definition in assets/games/<game>.json says
+//   files: the director files of the game, by the name its scripts use for them
+//   start_state and start_room: where the game goes once the files are loaded
+//   plugins: the screens and systems the game needs on top of the engine
+//   transparency: overrides per file and member number, for members that are only ever placed by us,
+//     every override says which of our screens places the member
+//   locales: per language code, the members of a file that replace those of the original release, see mulle_locale
+// The game is picked at launch with `game` in openmulle.toml or --game, the car game is the default
+
+const GAMES_DIR: &str = "games";
+
+/// Which white is transparent follows from the inks the game draws a member with,
+/// members that have no ink can have it set per file and member number
+pub type TransparencyOverrides = HashMap<String, HashMap<u32, TransparencyOverride>>;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TransparencyOverride {
+    pub transparency: Transparency,
+    /// Where we place the member without an ink
+    pub reason: String,
+}
+
+/// Per file, the member number of the original release and the one that replaces it
+pub type MemberOverrides = HashMap<String, HashMap<u32, u32>>;
+
+#[derive(Resource, Clone, Debug, Deserialize)]
+pub struct GameDefinition {
+    pub name: String,
+    pub files: Vec<String>,
+    pub start_state: GameState,
+    #[serde(default)]
+    pub start_room: String,
+    #[serde(default)]
+    pub plugins: Vec<GamePlugin>,
+    #[serde(default)]
+    pub transparency: TransparencyOverrides,
+    #[serde(default)]
+    pub locales: HashMap<String, MemberOverrides>,
+}
+
+/// The parts of the game that not every game has
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamePlugin {
+    Car,
+    WorldDrive,
+    Garage,
+    Rooms,
+}
+
+impl GameDefinition {
+    pub fn load(settings: &LaunchSettings) -> Result<Self, String> {
+        let path = settings
+            .assets_dir
+            .join(GAMES_DIR)
+            .join(format!("{}.json", settings.game));
+        let contents = fs::read_to_string(&path)
+            .map_err(|error| format!("can not read {}: {error}", path.display()))?;
+        serde_json::from_str(&contents)
+            .map_err(|error| format!("can not read {}: {error}", path.display()))
+    }
+
+    pub fn has_plugin(&self, plugin: GamePlugin) -> bool {
+        self.plugins.contains(&plugin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cars_definition_parses() {
+        let game: GameDefinition =
+            serde_json::from_str(include_str!("../assets/games/cars.json")).unwrap();
+        assert!(game.has_plugin(GamePlugin::Rooms));
+        for overrides in game.transparency.values() {
+            assert!(overrides
+                .values()
+                .all(|transparency_override| !transparency_override.reason.is_empty()));
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::io::{AssetSourceBuilder, AssetSourceId},
+    prelude::*,
+};
+use serde::Deserialize;
+
+// Where the files of the game and our own files are, read from openmulle.toml in the working directory
+// and overridden by the command line:
+//   --data <dir>    the folder with the director files, usually MOVIES on the game disc or in its install folder
+//   --assets <dir>  the files that ship with openMulle: rooms.json, the game definitions and the fonts
+//   --game <game>   which definition in assets/games to run, see game_definition
+//   --locale <code> the language of the release, when guessing it from its texts goes wrong, see mulle_locale
+//   --editor        F2 opens the room editor, see yard/editor
+//   --rebuild-cache decode the files again instead of using what was cached in assets/cache, see decode_cache
+// Without either the director files are expected in the assets folder, like they always were
+
+const SETTINGS_FILE: &str = "openmulle.toml";
+// in the assets folder, the director files can be on a read-only disc
+const CACHE_DIR: &str = "cache";
+
+/// The asset source the director files are loaded from, so "game://02.dxr" is a file in the data folder
+pub const GAME_DATA_SOURCE: &str = "game";
+
+#[derive(Resource, Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct LaunchSettings {
+    pub assets_dir: PathBuf,
+    data_dir: Option<PathBuf>,
+    pub game: String,
+    pub locale: Option<String>,
+    pub editor: bool,
+    pub rebuild_cache: bool,
+}
+
+impl Default for LaunchSettings {
+    fn default() -> Self {
+        Self {
+            assets_dir: PathBuf::from("assets"),
+            data_dir: None,
+            game: "cars".to_owned(),
+            locale: None,
+            editor: false,
+            rebuild_cache: false,
+        }
+    }
+}
+
+impl LaunchSettings {
+    /// Reads the settings file and the command line, anything wrong with them is reported and left at its default
+    pub fn load() -> Self {
+        let mut settings = fs::read_to_string(SETTINGS_FILE).map_or_else(
+            |_| Self::default(), // the file is optional
+            |contents| {
+                toml::from_str(&contents).unwrap_or_else(|error| {
+                    eprintln!("ignoring {SETTINGS_FILE}: {error}");
+                    Self::default()
+                })
+            },
+        );
+        settings.apply_args(env::args().skip(1));
+        settings.make_absolute();
+        settings
+    }
+
+    fn apply_args(&mut self, mut args: impl Iterator<Item = String>) {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--data" | "--assets" | "--game" | "--locale" => {
+                    let Some(value) = args.next() else {
+                        eprintln!("ignoring {arg}, it needs a value");
+                        break;
+                    };
+                    match arg.as_str() {
+                        "--data" => self.data_dir = Some(PathBuf::from(value)),
+                        "--assets" => self.assets_dir = PathBuf::from(value),
+                        "--locale" => self.locale = Some(value),
+                        _ => self.game = value,
+                    }
+                }
+                "--editor" => self.editor = true,
+                "--rebuild-cache" => self.rebuild_cache = true,
+                _ => eprintln!("ignoring argument {arg}"),
+            }
+        }
+    }
+
+    /// Bevy finds relative folders next to the binary and openMulle itself in the working directory, made absolute
+    /// once they are the same folder for both
+    fn make_absolute(&mut self) {
+        for dir in std::iter::once(&mut self.assets_dir).chain(self.data_dir.as_mut()) {
+            if let Ok(absolute) = std::path::absolute(&*dir) {
+                *dir = absolute;
+            }
+        }
+    }
+
+    /// The folder with the director files
+    pub fn data_dir(&self) -> &Path {
+        self.data_dir.as_deref().unwrap_or(&self.assets_dir)
+    }
+
+    /// Where decoded members are kept between launches
+    pub fn cache_dir(&self) -> PathBuf {
+        self.assets_dir.join(CACHE_DIR)
+    }
+
+    /// The asset plugin reads our own files from the assets folder
+    pub fn asset_plugin(&self) -> AssetPlugin {
+        AssetPlugin {
+            file_path: self.assets_dir.to_string_lossy().into_owned(),
+            ..default()
+        }
+    }
+
+    /// Has to be registered before the asset plugin is added
+    pub fn game_data_source(&self) -> (AssetSourceId<'static>, AssetSourceBuilder) {
+        (
+            AssetSourceId::from(GAME_DATA_SOURCE),
+            AssetSourceBuilder::platform_default(&self.data_dir().to_string_lossy(), None),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> LaunchSettings {
+        let mut settings = LaunchSettings::default();
+        settings.apply_args(args.iter().map(ToString::to_string));
+        settings
+    }
+
+    #[test]
+    fn command_line() {
+        let settings = args(&[
+            "--data",
+            "/media/cdrom/MOVIES",
+            "--game",
+            "boats",
+            "--wat",
+            "--locale",
+            "nl",
+            "--editor",
+            "--rebuild-cache",
+        ]);
+        assert_eq!(settings.data_dir(), Path::new("/media/cdrom/MOVIES"));
+        assert_eq!(settings.game, "boats");
+        assert_eq!(settings.locale.as_deref(), Some("nl"));
+        assert!(settings.editor);
+        assert!(settings.rebuild_cache);
+        assert_eq!(settings.assets_dir, Path::new("assets"));
+    }
+
+    #[test]
+    fn missing_values_are_left_out() {
+        let settings = args(&["--game"]);
+        assert_eq!(settings.game, "cars");
+        // without a data folder the director files are in the assets
+        assert_eq!(settings.data_dir(), Path::new("assets"));
+        assert!(!settings.editor);
+    }
+
+    #[test]
+    fn settings_file() {
+        let settings: LaunchSettings =
+            toml::from_str("data_dir = \"MOVIES\"\nrebuild_cache = true").unwrap();
+        assert_eq!(settings.data_dir(), Path::new("MOVIES"));
+        assert!(settings.rebuild_cache);
+        assert_eq!(settings.game, "cars");
+    }
+
+    #[test]
+    fn relative_folders_become_absolute() {
+        let mut settings = args(&["--data", "MOVIES"]);
+        settings.make_absolute();
+        let working_dir = env::current_dir().unwrap();
+        assert_eq!(settings.data_dir(), working_dir.join("MOVIES"));
+        assert_eq!(settings.assets_dir, working_dir.join("assets"));
+        assert_eq!(
+            settings.cache_dir(),
+            working_dir.join("assets").join("cache")
+        );
+    }
+}
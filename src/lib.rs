@@ -0,0 +1,30 @@
+#![warn(clippy::nursery, clippy::unwrap_used, clippy::style)]
+#![allow(clippy::unwrap_used)]
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+pub mod game_definition;
+pub mod launch;
+pub mod parsers;
+pub mod render;
+pub mod screens;
+pub mod systems;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, States, Serialize, Deserialize)]
+pub enum GameState {
+    #[default]
+    None,
+    DaHood,
+    GarageWithoutCar,
+    GarageWithCar,
+    YardWithoutCar,
+    YardWithCar,
+    TrashHeap,
+    Room,
+}
+
+// Generic system that takes a component as a parameter, and will despawn all entities with that component
+fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
+    for entity in &to_despawn {
+        commands.entity(entity).despawn_recursive();
+    }
+}
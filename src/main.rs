@@ -1,48 +1,56 @@
 #![warn(clippy::nursery, clippy::unwrap_used, clippy::style)]
 #![allow(clippy::unwrap_used)]
 use bevy::prelude::*;
-use serde::{Deserialize, Serialize};
-mod parsers;
-mod render;
-mod screens;
-mod systems;
+use open_mulle::{
+    game_definition::{GameDefinition, GamePlugin},
+    launch::LaunchSettings,
+    render, screens, systems, GameState,
+};
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
+    let settings = LaunchSettings::load();
+    let game = match GameDefinition::load(&settings) {
+        Ok(game) => game,
+        Err(error) => {
+            eprintln!("{error}");
+            return;
+        }
+    };
+    println!("starting {}", game.name);
+
+    let editor = settings.editor && game.has_plugin(GamePlugin::Rooms);
+    let (game_data_id, game_data_source) = settings.game_data_source();
+    let mut app = App::new();
+    app.register_asset_source(game_data_id, game_data_source)
+        .add_plugins(DefaultPlugins.set(settings.asset_plugin()))
+        .insert_resource(settings)
+        .insert_resource(game.clone())
         .init_state::<GameState>()
         .add_plugins(systems::mulle_asset_helper::MulleAssetHelperPlugin)
         .add_plugins(render::scaler::ScalerPlugin)
         .add_plugins(systems::mulle_point_and_click::MullePointandClickPlugin)
-        .add_plugins(systems::mulle_car::MulleCarPlugin)
-        .add_plugins(screens::world_drive::WorldDrivePlugin)
-        .add_plugins(screens::garage::GaragePlugin)
-        .add_plugins(screens::yard::YardPlugin)
+        .add_plugins(systems::mulle_lingo::MulleLingoPlugin)
+        .add_plugins(systems::mulle_filmloop::MulleFilmloopPlugin)
+        .add_plugins(systems::mulle_text::MulleTextPlugin)
         // .add_plugins(screens::trash_heap::TrashHeapPlugin)
-        .add_systems(PostStartup, set_init)
-        .run();
-}
-
-fn set_init(mut game_state: ResMut<NextState<GameState>>) {
-    game_state.set(GameState::Room);
-}
-
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, States, Serialize, Deserialize)]
-enum GameState {
-    None,
-    #[default]
-    DaHood,
-    GarageWithoutCar,
-    GarageWithCar,
-    YardWithoutCar,
-    YardWithCar,
-    TrashHeap,
-    Room,
+        .add_systems(
+            OnEnter(systems::mulle_asset_helper::MulleAssetState::Loaded),
+            set_init,
+        );
+    for plugin in &game.plugins {
+        match plugin {
+            GamePlugin::Car => app.add_plugins(systems::mulle_car::MulleCarPlugin),
+            GamePlugin::WorldDrive => app.add_plugins(screens::world_drive::WorldDrivePlugin),
+            GamePlugin::Garage => app.add_plugins(screens::garage::GaragePlugin),
+            GamePlugin::Rooms => app.add_plugins(screens::yard::YardPlugin),
+        };
+    }
+    if editor {
+        app.add_plugins(screens::yard::RoomEditorPlugin);
+    }
+    app.run();
 }
 
-// Generic system that takes a component as a parameter, and will despawn all entities with that component
-fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
-    for entity in &to_despawn {
-        commands.entity(entity).despawn_recursive();
-    }
+fn set_init(mut game_state: ResMut<NextState<GameState>>, game: Res<GameDefinition>) {
+    game_state.set(game.start_state);
 }
@@ -1 +1,2 @@
 pub mod database_language;
+pub mod director;
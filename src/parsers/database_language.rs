@@ -14,6 +14,8 @@ use nom::{
     IResult,
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::systems::mulle_car::{PartDB, PartNew};
 
 // #[derive(Debug)]
@@ -25,8 +27,8 @@ use crate::systems::mulle_car::{PartDB, PartNew};
 #[derive(Clone)]
 pub enum Value {
     Number(i32),
-    Array(Vec<(String, Value)>),
-    ArraySingle(Vec<Value>),
+    Array(Vec<(String, Self)>),
+    ArraySingle(Vec<Self>),
     String(String),
     Tag(String),
     Point((i32, i32)),
@@ -52,10 +54,7 @@ fn parse_array_like_structure(input: &str) -> IResult<&str, Value> {
                 char(']'),
             ),
         ),
-        |s| match s {
-            Some(s) => Value::ArraySingle(s),
-            None => Value::Nothing(),
-        },
+        |s| s.map_or(Value::Nothing(), Value::ArraySingle),
     )(input)
 }
 
@@ -262,10 +261,7 @@ fn parse_opt_value(input: &str) -> IResult<&str, Option<Value>> {
 }
 
 fn parse_key_value(input: &str) -> IResult<&str, (String, Value)> {
-    parse_key_t(input, &parse_opt_value, |s2| match s2 {
-        None => Value::Nothing(),
-        Some(s) => s,
-    })
+    parse_key_t(input, &parse_opt_value, |s2| s2.unwrap_or(Value::Nothing()))
 }
 
 fn parse_key_stringvalue(input: &str) -> IResult<&str, (String, String)> {
@@ -478,7 +474,7 @@ fn parse_key_newvalue(input: &str) -> IResult<&str, (String, Vec<PartNew>)> {
         ),
         |(tag, tuple)| {
             let mut vec = Vec::<PartNew>::new();
-            for value in &tuple {
+            if let Some(value) = &tuple {
                 for value in value.iter().flatten() {
                     for value in value {
                         vec.push(PartNew {
@@ -516,7 +512,7 @@ fn parse_key_tagarray(input: &str) -> IResult<&str, (String, Vec<String>)> {
         ),
         |(tag, tuple)| {
             let mut vec = Vec::<String>::new();
-            for value in &tuple {
+            if let Some(value) = &tuple {
                 for value in value {
                     vec.push((*value).to_owned());
                 }
@@ -536,31 +532,31 @@ pub fn parse_dictish_structure(input: &str) -> IResult<&str, Value> {
     )(input)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MapData {
     pub map_id: i32,
     pub objects: Vec<Object>,
     pub map_image: String,
     pub topology: String,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 enum HillType {
     SmallHill,
     BigHill,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 enum InnerValue {
     InnerRadius(i32),
     Show(i32),
     HillType(HillType),
     Direction(i32),
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Object {
     id: i32,
     point: Point,
@@ -716,6 +712,7 @@ struct Action {
     anim_type: Option<MulleAnimationActionType>,
     sub_actions: Vec<MulleAnimationAction>,
 }
+#[allow(non_camel_case_types)] // named after the tags used in the AnimCharts
 enum MulleAnimationActionType {
     Still,
     Wait,
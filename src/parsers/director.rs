@@ -0,0 +1,938 @@
+mod afterburner;
+pub mod bitmap;
+pub mod config;
+mod error;
+pub mod ink;
+pub mod lingo;
+pub mod palette;
+mod reader;
+pub mod score;
+pub mod shape;
+pub mod sound;
+pub mod text;
+
+use std::{
+    collections::HashMap,
+    io::{self, Cursor, Read, Seek},
+};
+
+use serde::{Deserialize, Serialize};
+use yore::code_pages::CP1252;
+
+pub use error::AssetParseError;
+use palette::PaletteRef;
+pub use reader::ChunkReader;
+
+// A Director file (.dxr/.cxt) is a RIFX container, the container itself is either big or little endian
+// the chunks inside the container are mostly big endian regardless of the container
+// Shockwave files (.dcr/.cct) are compressed with Afterburner, their chunks are decompressed while opening
+// This module only knows about the container and its cast members, it does not depend on bevy
+
+/// Byte order of the data, RIFX files are big endian and XFIR files are little endian
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+// the container header and the memory map are listed in the memory map themselves, errors in them use these slots
+const HEADER_SLOT: u32 = 0;
+const MMAP_SLOT: u32 = 2;
+// later director versions add member types past the ones listed on MacromediaCastMember
+const MAX_MEMBER_TYPE: u32 = 16;
+
+type AfterburnerChunkModel = (
+    MacromediaFileHeader,
+    MacromediaFileHeaderMmap,
+    Vec<MacromediaSubFile>,
+    HashMap<u32, Result<Vec<u8>, String>>,
+);
+
+#[allow(dead_code)]
+pub struct DirectorFile<R> {
+    pub name: String,
+    reader: ChunkReader<R>,
+    endian: Endianness,
+    pub header: MacromediaFileHeader,
+    pub mmap: MacromediaFileHeaderMmap,
+    pub sub_files: Vec<MacromediaSubFile>,
+    /// Decompressed chunks by slot for Afterburner files, `Err` has the compression that could not be undone
+    chunks: HashMap<u32, Result<Vec<u8>, String>>,
+    pub cast_libraries: HashMap<u32, MacromediaCastLibrary>,
+    /// `None` when the file has no config chunk or it could not be read
+    pub config: Option<config::MovieConfig>,
+    pub members: Vec<MacromediaCastMember>,
+    /// Members that could not be read, the rest of the file is still usable
+    pub member_errors: Vec<AssetParseError>,
+}
+
+impl<R: Read + Seek> DirectorFile<R> {
+    /// Reads the container header, the memory map, the key table and all cast member headers
+    /// `name` is only used to tell where errors came from
+    pub fn open(name: &str, file: R) -> Result<Self, AssetParseError> {
+        let truncated_header = AssetParseError::truncated(name, HEADER_SLOT);
+        let mut reader = ChunkReader::new(file, Endianness::Big).map_err(truncated_header)?;
+
+        let magic = reader
+            .fourcc()
+            .map_err(AssetParseError::truncated(name, HEADER_SLOT))?;
+        let endian = match &magic {
+            b"RIFX" => Endianness::Big,
+            b"XFIR" => Endianness::Little,
+            _ => {
+                return Err(AssetParseError::BadMagic {
+                    file: name.to_owned(),
+                    magic,
+                })
+            }
+        };
+        reader.set_endian(endian);
+
+        let file_size = reader
+            .u32()
+            .map_err(AssetParseError::truncated(name, HEADER_SLOT))?;
+        let file_sign = reader
+            .fourcc()
+            .map_err(AssetParseError::truncated(name, HEADER_SLOT))?;
+
+        let (header, mmap, sub_files, chunks) = if afterburner::is_afterburner(&file_sign) {
+            let movie = afterburner::read_afterburner(&mut reader)
+                .map_err(AssetParseError::truncated(name, HEADER_SLOT))?;
+            Self::afterburner_chunk_model(file_size, file_sign, movie)
+        } else {
+            let header = Self::read_header(&mut reader, file_size, file_sign)
+                .map_err(AssetParseError::truncated(name, HEADER_SLOT))?;
+
+            if reader.seek(u64::from(header.mmap_offset)).is_err() {
+                return Err(AssetParseError::BadOffset {
+                    file: name.to_owned(),
+                    chunk: MMAP_SLOT,
+                    offset: u64::from(header.mmap_offset),
+                });
+            }
+
+            let (mmap, sub_files) = Self::read_mmap(&mut reader)
+                .map_err(AssetParseError::truncated(name, MMAP_SLOT))?;
+            (header, mmap, sub_files, HashMap::new())
+        };
+
+        let mut director_file = Self {
+            name: name.to_owned(),
+            reader,
+            endian,
+            header,
+            mmap,
+            sub_files,
+            chunks,
+            cast_libraries: HashMap::new(),
+            config: None,
+            members: Vec::new(),
+            member_errors: Vec::new(),
+        };
+
+        match director_file.read_config() {
+            Ok(config) => director_file.config = config,
+            Err(error) => director_file.member_errors.push(error),
+        }
+
+        let mut linked_entries = director_file.read_key_table()?;
+
+        for (cast_num, cast_library) in &mut director_file.cast_libraries {
+            if let Some(library_entries) = linked_entries.get(&(cast_num + 1024)) {
+                cast_library.linked_entries.clone_from(library_entries);
+            }
+        }
+
+        let mut cast_members = Vec::<(u32, u32)>::new(); // These should be only one member list per library?
+
+        let library_slots: Vec<u32> = director_file
+            .cast_libraries
+            .values()
+            .map(|cast_library| cast_library.lib_slot)
+            .collect();
+        for lib_slot in library_slots {
+            // the CAS* contents are always BE
+            let mut cas_star = director_file.chunk(lib_slot)?;
+
+            for i in 0..(cas_star.len() / 4) as u32 {
+                let cast_slot = cas_star
+                    .u32()
+                    .map_err(AssetParseError::truncated(name, lib_slot))?;
+
+                if cast_slot != 0 {
+                    // no need to store 0 reference
+                    cast_members.push((i + 1, cast_slot));
+                }
+            }
+        }
+
+        for (number, slot) in cast_members {
+            let linked_entries = linked_entries.remove(&slot).unwrap_or_default();
+            match director_file.read_member(number, slot, linked_entries) {
+                Ok(member) => director_file.members.push(member),
+                Err(error) => director_file.member_errors.push(error),
+            }
+        }
+
+        Ok(director_file)
+    }
+
+    fn read_header(
+        reader: &mut ChunkReader<R>,
+        file_size: u32,
+        file_sign: [u8; 4],
+    ) -> io::Result<MacromediaFileHeader> {
+        Ok(MacromediaFileHeader {
+            file_size,
+            file_sign,
+            imap: reader.fourcc()?,
+            imap_length: reader.u32()?,
+            imap_unknown: reader.u32()?,
+            mmap_offset: reader.u32()?,
+        })
+    }
+
+    /// Lays the chunks of an Afterburner file out like a memory map, slots without a chunk are listed as free
+    fn afterburner_chunk_model(
+        file_size: u32,
+        file_sign: [u8; 4],
+        movie: afterburner::AfterburnerMovie,
+    ) -> AfterburnerChunkModel {
+        let slots = movie
+            .chunks
+            .keys()
+            .next_back()
+            .map_or(0, |last_slot| last_slot + 1);
+        let mut sub_files: Vec<MacromediaSubFile> =
+            (0..slots).map(|_| MacromediaSubFile::free()).collect();
+        let mut chunks = HashMap::new();
+        for (slot, chunk) in movie.chunks {
+            sub_files[slot as usize] = MacromediaSubFile {
+                entry_type: chunk.fourcc,
+                entry_length: chunk.length,
+                ..MacromediaSubFile::free()
+            };
+            chunks.insert(slot, chunk.data);
+        }
+
+        let header = MacromediaFileHeader {
+            file_size,
+            file_sign,
+            imap: *b"ABMP",
+            imap_length: 0,
+            imap_unknown: 0,
+            mmap_offset: 0,
+        };
+        let mmap = MacromediaFileHeaderMmap {
+            mmap: *b"ABMP",
+            mmap_length: 0,
+            version: movie.version,
+            unknown1: 0,
+            amount_of_files: slots,
+            unknown2: 0,
+            unknown3: 0,
+            unknown4: 0,
+        };
+        (header, mmap, sub_files, chunks)
+    }
+
+    fn read_mmap(
+        reader: &mut ChunkReader<R>,
+    ) -> io::Result<(MacromediaFileHeaderMmap, Vec<MacromediaSubFile>)> {
+        let mmap = MacromediaFileHeaderMmap {
+            mmap: reader.fourcc()?,
+            mmap_length: reader.u32()?,
+            version: reader.u32()?,
+            unknown1: reader.u32()?,
+            amount_of_files: reader.u32()?,
+            unknown2: reader.u32()?,
+            unknown3: reader.u32()?,
+            unknown4: reader.u32()?,
+        };
+
+        let mut sub_files = Vec::<MacromediaSubFile>::new();
+
+        for _ in 0..mmap.amount_of_files {
+            sub_files.push(MacromediaSubFile {
+                entry_type: reader.fourcc()?,
+                entry_length: reader.u32()?,
+                entry_offset: reader.u32()?,
+                _unknown1: reader.u32()?,
+                _index: reader.u32()?,
+            });
+        }
+        Ok((mmap, sub_files))
+    }
+
+    fn read_member(
+        &mut self,
+        number: u32,
+        slot: u32,
+        linked_entries: Vec<u32>,
+    ) -> Result<MacromediaCastMember, AssetParseError> {
+        // the CASt contents are always BE
+        let mut cast = self.chunk(slot)?;
+        let truncated = || AssetParseError::truncated(&self.name, slot);
+
+        let cast_type = cast.u32().map_err(truncated())?;
+        if cast_type == 0 || cast_type > MAX_MEMBER_TYPE {
+            return Err(AssetParseError::UnknownMemberType {
+                file: self.name.clone(),
+                chunk: slot,
+                member_type: cast_type,
+            });
+        }
+        let info_length = cast.u32().map_err(truncated())?;
+        let specific_data_length = cast.u32().map_err(truncated())?;
+
+        let info_data = cast.bytes(info_length as usize).map_err(truncated())?;
+        let specific_data = cast
+            .bytes(specific_data_length as usize)
+            .map_err(truncated())?;
+
+        Ok(MacromediaCastMember {
+            number,
+            slot,
+            cast_type,
+            name: read_member_name(&info_data).map_err(truncated())?,
+            script_id: read_member_script_id(&info_data).map_err(truncated())?,
+            specific_data,
+            linked_entries,
+        })
+    }
+
+    /// Fills the cast libraries from the KEY* table and returns the chunks owned by every other slot
+    fn read_key_table(&mut self) -> Result<HashMap<u32, Vec<u32>>, AssetParseError> {
+        let mut linked_entries = HashMap::<u32, Vec<u32>>::new();
+
+        let key_slots: Vec<u32> = (0..self.sub_files.len() as u32)
+            .filter(|slot| self.sub_files[*slot as usize].fourcc() == "KEY*")
+            .collect();
+
+        for key_slot in key_slots {
+            let mut key_table = self.chunk(key_slot)?;
+            // unlike most chunks the KEY* follows the container
+            key_table.set_endian(self.endian);
+            let entries = read_key_entries(&mut key_table)
+                .map_err(AssetParseError::truncated(&self.name, key_slot))?;
+
+            for (cast_file_slot, cast_slot, cast_type) in entries {
+                if cast_slot >= 1024 && cast_type == "CAS*" {
+                    self.cast_libraries.insert(
+                        cast_slot - 1024,
+                        MacromediaCastLibrary {
+                            lib_slot: cast_file_slot,
+                            linked_entries: Vec::new(),
+                        },
+                    );
+                } else {
+                    linked_entries
+                        .entry(cast_slot)
+                        .or_default()
+                        .push(cast_file_slot);
+                }
+            }
+        }
+        Ok(linked_entries)
+    }
+
+    /// A big endian reader bounded to the contents of the chunk in slot `slot`, without the fourcc and length
+    fn chunk_sub_reader(&mut self, slot: u32) -> io::Result<ChunkReader<&mut R>> {
+        let subfile = self.sub_files.get(slot as usize).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("chunk slot {slot} does not exist"),
+            )
+        })?;
+        let expected_type = subfile.entry_type;
+        let expected_length = subfile.entry_length;
+        let entry_offset = u64::from(subfile.entry_offset);
+
+        self.reader.seek(entry_offset)?;
+        let entry_type = self.reader.fourcc()?;
+        let entry_length = self.reader.u32()?;
+        if entry_type != expected_type {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "expected a {} chunk at {entry_offset} but found {}",
+                    CP1252.decode(&expected_type),
+                    CP1252.decode(&entry_type)
+                ),
+            ));
+        }
+
+        let mut sub_reader = self.reader.sub_reader(
+            entry_offset + 8,
+            u64::from(entry_length.min(expected_length)),
+        )?; // +8 to skip the fourcc
+        sub_reader.set_endian(Endianness::Big);
+        Ok(sub_reader)
+    }
+
+    /// A big endian reader over a copy of the chunk in slot `slot`
+    pub fn chunk(&mut self, slot: u32) -> Result<ChunkReader<Cursor<Vec<u8>>>, AssetParseError> {
+        let buffer = self.read_chunk(slot)?;
+        ChunkReader::new(Cursor::new(buffer), Endianness::Big)
+            .map_err(AssetParseError::truncated(&self.name, slot))
+    }
+
+    /// Reads the contents of the chunk in slot `slot`, without the fourcc and length
+    pub fn read_chunk(&mut self, slot: u32) -> Result<Vec<u8>, AssetParseError> {
+        if let Some(chunk) = self.chunks.get(&slot) {
+            return chunk
+                .clone()
+                .map_err(|compression| AssetParseError::UnsupportedFormat {
+                    file: self.name.clone(),
+                    chunk: slot,
+                    format: format!("chunk compressed with {compression}"),
+                });
+        }
+        let offset = self
+            .sub_files
+            .get(slot as usize)
+            .map_or(0, |subfile| u64::from(subfile.entry_offset));
+        let name = self.name.clone();
+        let bad_offset = |_| AssetParseError::BadOffset {
+            file: name.clone(),
+            chunk: slot,
+            offset,
+        };
+        let mut sub_reader = self.chunk_sub_reader(slot).map_err(bad_offset)?;
+        let length = sub_reader.len() as usize;
+        sub_reader
+            .bytes(length)
+            .map_err(AssetParseError::truncated(&name, slot))
+    }
+
+    /// The slot of the first chunk of type `fourcc`, for chunks a movie only has one of
+    pub fn find_chunk(&self, fourcc: &str) -> Option<u32> {
+        self.sub_files
+            .iter()
+            .position(|subfile| subfile.fourcc() == fourcc)
+            .map(|slot| slot as u32)
+    }
+
+    /// The score and frame labels of the movie, casts (.cxt) do not have one
+    pub fn score(&mut self) -> Result<Option<score::MacromediaScore>, AssetParseError> {
+        let Some(score_slot) = self.find_chunk("VWSC") else {
+            return Ok(None);
+        };
+        let vwsc = self.read_chunk(score_slot)?;
+        let vwlb = match self.find_chunk("VWLB") {
+            Some(labels_slot) => Some(self.read_chunk(labels_slot)?),
+            None => None,
+        };
+        score::read_score(&vwsc, vwlb.as_deref())
+            .map(Some)
+            .map_err(AssetParseError::truncated(&self.name, score_slot))
+    }
+
+    fn read_config(&mut self) -> Result<Option<config::MovieConfig>, AssetParseError> {
+        let Some(config_slot) = self.find_chunk("DRCF").or_else(|| self.find_chunk("VWCF")) else {
+            return Ok(None);
+        };
+        let data = self.read_chunk(config_slot)?;
+        config::read_config(&data)
+            .map(Some)
+            .map_err(AssetParseError::truncated(&self.name, config_slot))
+    }
+
+    /// The cast libraries of the movie in the order lingo numbers them, casts (.cxt) do not have a list
+    pub fn cast_list(&mut self) -> Result<Vec<MacromediaCastListEntry>, AssetParseError> {
+        let Some(list_slot) = self.find_chunk("MCsL") else {
+            return Ok(Vec::new());
+        };
+        let data = self.read_chunk(list_slot)?;
+        read_cast_list(&data).map_err(AssetParseError::truncated(&self.name, list_slot))
+    }
+
+    /// The names of the fonts the style runs of text members refer to by number
+    pub fn font_map(&mut self) -> Result<HashMap<u16, String>, AssetParseError> {
+        let (map_slot, is_fmap) = match (self.find_chunk("Fmap"), self.find_chunk("VWFM")) {
+            (Some(slot), _) => (slot, true),
+            (None, Some(slot)) => (slot, false),
+            (None, None) => return Ok(HashMap::new()),
+        };
+        let data = self.read_chunk(map_slot)?;
+        if is_fmap {
+            text::read_fmap(&data)
+        } else {
+            text::read_vwfm(&data)
+        }
+        .map_err(AssetParseError::truncated(&self.name, map_slot))
+    }
+
+    /// The compiled scripts of the movie, scripts that fail to read end up in `member_errors`
+    pub fn lingo(&mut self) -> Result<Option<lingo::LingoContext>, AssetParseError> {
+        let (context_slot, capital_x) = match (self.find_chunk("LctX"), self.find_chunk("Lctx")) {
+            (Some(slot), _) => (slot, true),
+            (None, Some(slot)) => (slot, false),
+            (None, None) => return Ok(None),
+        };
+        let context = self.read_chunk(context_slot)?;
+        let context_map = lingo::read_context_map(&context)
+            .map_err(AssetParseError::truncated(&self.name, context_slot))?;
+
+        let names = match context_map.names_slot {
+            Some(names_slot) => lingo::read_names(&self.read_chunk(names_slot)?)
+                .map_err(AssetParseError::truncated(&self.name, names_slot))?,
+            None => Vec::new(),
+        };
+
+        // without a config the handlers are assumed to be older than director 8.5
+        let director_version = self
+            .config
+            .as_ref()
+            .map_or(0, config::MovieConfig::director_version);
+        let mut scripts = HashMap::new();
+        for (number, script_slot) in context_map.script_slots {
+            let script = self.read_chunk(script_slot).and_then(|data| {
+                lingo::read_script(&data, &names, director_version)
+                    .map_err(AssetParseError::truncated(&self.name, script_slot))
+            });
+            match script {
+                Ok(script) => {
+                    scripts.insert(number, script);
+                }
+                Err(error) => self.member_errors.push(error),
+            }
+        }
+        Ok(Some(lingo::LingoContext::new(
+            names,
+            scripts,
+            capital_x,
+            director_version,
+        )))
+    }
+
+    /// The fourcc and slot of every chunk that belongs to the member
+    pub fn linked_chunks(&self, member: &MacromediaCastMember) -> Vec<(String, u32)> {
+        member
+            .linked_entries
+            .iter()
+            .filter_map(|slot| {
+                self.sub_files
+                    .get(*slot as usize)
+                    .map(|subfile| (subfile.fourcc(), *slot))
+            })
+            .collect()
+    }
+}
+
+// the MCsL is a list of items, every cast library takes up `items_per_cast` of them after the first item
+fn read_cast_list(data: &[u8]) -> io::Result<Vec<MacromediaCastListEntry>> {
+    let mut list = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+    let data_offset = list.u32()?;
+    list.skip(2)?; // unknown
+    let cast_count = list.u16()?;
+    let items_per_cast = usize::from(list.u16()?);
+
+    list.seek(u64::from(data_offset))?;
+    let item_count = list.u16()?;
+    let mut item_offsets = Vec::<u32>::new();
+    for _ in 0..item_count {
+        item_offsets.push(list.u32()?);
+    }
+    let items_length = list.u32()?;
+    let items_start = list.position()?;
+
+    let mut items = Vec::<Vec<u8>>::new();
+    for (index, offset) in item_offsets.iter().enumerate() {
+        let end = item_offsets.get(index + 1).copied().unwrap_or(items_length);
+        list.seek(items_start + u64::from(*offset))?;
+        items.push(list.bytes(end.saturating_sub(*offset) as usize)?);
+    }
+
+    let item = |cast: usize, field: usize| {
+        if field > items_per_cast {
+            return &[][..];
+        }
+        items
+            .get(cast * items_per_cast + field)
+            .map_or(&[][..], Vec::as_slice)
+    };
+    let pascal_string = |item: &[u8]| {
+        let length = item.first().map_or(0, |length| usize::from(*length));
+        CP1252
+            .decode(item.get(1..=length).unwrap_or_default())
+            .to_string()
+    };
+
+    let mut entries = Vec::new();
+    for cast in 0..usize::from(cast_count) {
+        let mut entry = MacromediaCastListEntry {
+            name: pascal_string(item(cast, 1)),
+            file_path: pascal_string(item(cast, 2)),
+            min_member: 0,
+            max_member: 0,
+            id: 0,
+        };
+        let range = item(cast, 4);
+        if range.len() >= 8 {
+            let mut range = ChunkReader::new(Cursor::new(range), Endianness::Big)?;
+            entry.min_member = range.u16()?;
+            entry.max_member = range.u16()?;
+            entry.id = range.i32()?;
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn read_key_entries(
+    key_table: &mut ChunkReader<Cursor<Vec<u8>>>,
+) -> io::Result<Vec<(u32, u32, String)>> {
+    key_table.skip(8)?; // discarding this data since I don't know what it does
+    let amount_of_entries = key_table.u32()?;
+
+    let mut entries = Vec::<(u32, u32, String)>::new();
+    for _ in 0..amount_of_entries {
+        let cast_file_slot = key_table.u32()?;
+        let cast_slot = key_table.u32()?;
+        let cast_type = CP1252.decode(&key_table.fourcc()?).to_string();
+        entries.push((cast_file_slot, cast_slot, cast_type));
+    }
+    Ok(entries)
+}
+
+/// The number of the script in the script context that belongs to the member, 0 when it has none
+fn read_member_script_id(info_data: &[u8]) -> io::Result<u32> {
+    if info_data.is_empty() {
+        return Ok(0);
+    }
+    let mut info = ChunkReader::new(Cursor::new(info_data), Endianness::Big)?;
+    info.skip(16)?; // data offset and unknown fields
+    info.u32()
+}
+
+fn read_member_name(info_data: &[u8]) -> io::Result<Option<String>> {
+    if info_data.is_empty() {
+        return Ok(None);
+    }
+    let mut info = ChunkReader::new(Cursor::new(info_data), Endianness::Big)?;
+    info.skip(32)?; // gap of unknown data
+
+    let cast_member_num = info.u16()?;
+    let mut cast_member_field_offsets = Vec::<u32>::new();
+
+    for _ in 0..cast_member_num {
+        cast_member_field_offsets.push(info.u32()?);
+    }
+
+    let cast_member_field_data_length = info.u32()?;
+
+    let pre_member_field_pos = info.position()?;
+
+    for offset in cast_member_field_offsets {
+        info.seek(pre_member_field_pos + u64::from(offset))?;
+        let string_length = info.u8()?;
+        if string_length == 0 || u32::from(string_length) > cast_member_field_data_length {
+            continue;
+        }
+        // the first field that is set is the name
+        return Ok(Some(
+            CP1252
+                .decode(&info.bytes(string_length as usize)?)
+                .to_string(),
+        ));
+    }
+    Ok(None)
+}
+
+#[derive(Clone, Deserialize)]
+pub struct MacromediaSubFile {
+    pub entry_type: [u8; 4], // CP1252 encoded string
+    pub entry_length: u32,
+    pub entry_offset: u32,
+    _unknown1: u32,
+    // 3072 for 0 length, 0 offset
+    // 1024 for 0 length, any offset
+    // 0 for any length any offset
+    _index: u32, // index is only populated for 0 length
+}
+
+impl MacromediaSubFile {
+    /// An unused slot
+    const fn free() -> Self {
+        Self {
+            entry_type: *b"free",
+            entry_length: 0,
+            entry_offset: 0,
+            _unknown1: 0,
+            _index: 0,
+        }
+    }
+
+    pub fn fourcc(&self) -> String {
+        CP1252.decode(&self.entry_type).to_string()
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct MacromediaFileHeader {
+    pub file_size: u32,
+    pub file_sign: [u8; 4], // CP1252 string
+    pub imap: [u8; 4],      // CP1252 string
+    pub imap_length: u32,
+    pub imap_unknown: u32,
+    pub mmap_offset: u32,
+}
+
+#[derive(Clone, Deserialize)]
+#[allow(dead_code)]
+pub struct MacromediaFileHeaderMmap {
+    pub mmap: [u8; 4], // CP1252 string
+    pub mmap_length: u32,
+    pub version: u32, // not the director version, that is in the config chunk
+    pub unknown1: u32,
+    pub amount_of_files: u32,
+    pub unknown2: u32,
+    pub unknown3: u32,
+    pub unknown4: u32,
+}
+
+/// A cast library as the movie lists it, external casts have the path of their file
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub struct MacromediaCastListEntry {
+    pub name: String,
+    /// Empty for casts inside the movie, otherwise the path on the machine the movie was made on
+    pub file_path: String,
+    pub min_member: u16,
+    pub max_member: u16,
+    /// The slot of the CAS* in the key table is 1024 + this for internal casts
+    pub id: i32,
+}
+
+#[allow(dead_code)]
+pub struct MacromediaCastLibrary {
+    pub lib_slot: u32,
+    pub linked_entries: Vec<u32>,
+}
+
+pub struct MacromediaCastMember {
+    pub number: u32,
+    pub slot: u32,
+    // Known types and details
+    // 1: bitmap_metadata
+    // 2: filmloop?
+    // 3: field?
+    // 4: Palette
+    // 5: Picture?
+    // 6: Audio (file? metadata?), Member field may contain hints towards audio format
+    // 7: button, a text member with a button type, see shape::read_button
+    // 8: shape, see shape::read_shape
+    // 9: movie
+    // 10: digitalvideo
+    // 11: scripts, the bytecode is in the script context at script_id
+    // 12: Rich text, the plain text is in the RTE1
+    // 13: OLE?
+    // 14: Transition
+    pub cast_type: u32,
+    pub name: Option<String>,
+    pub script_id: u32,
+    pub specific_data: Vec<u8>,
+    pub linked_entries: Vec<u32>,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug)]
+pub struct MacromediaCastBitmapMetadata {
+    v27: u16,
+    pub image_pos_y: i16,
+    pub image_pos_x: i16,
+    pub image_height: i16, // appearently you need to subtract the pos elements of these to get the correct value?
+    pub image_width: i16, // appearently you need to subtract the pos elements of these to get the correct value?
+    // not an alpha treshold, the rect the bitmap was cropped from, there is no transparency in the member itself
+    _bounding_rect: [i16; 4],
+    pub image_reg_y: i16, // appearently you need to subtract the pos elements of these to get the correct value?
+    pub image_reg_x: i16, // appearently you need to subtract the pos elements of these to get the correct value?
+    // possibly the data ends here if it's 1-but, but is it padded to fit?
+    pub image_bit_depth: u8,
+    flags: u8,
+    pub palette: PaletteRef,
+}
+
+impl MacromediaCastBitmapMetadata {
+    /// Reads the bitmap metadata from the specific data of a type 1 cast member
+    pub fn read(specific_data: &[u8]) -> io::Result<Self> {
+        let mut file = ChunkReader::new(Cursor::new(specific_data), Endianness::Big)?; // image struct is always BE!
+        let unknown1 = file.u16()?; //V27?? the row length, the high bit tells if the depth and palette follow
+
+        let image_pos_y = file.i16()?;
+        let image_pos_x = file.i16()?;
+
+        let mut metadata = Self {
+            v27: unknown1,
+            image_pos_y,
+            image_pos_x,
+            image_height: file.i16()? - image_pos_y,
+            image_width: file.i16()? - image_pos_x,
+            _bounding_rect: [file.i16()?, file.i16()?, file.i16()?, file.i16()?],
+            image_reg_y: file.i16()? - image_pos_y,
+            image_reg_x: file.i16()? - image_pos_x,
+            flags: 0,
+            image_bit_depth: 1,
+            palette: PaletteRef::SystemMac,
+        };
+        if unknown1 & 0x8000 == 0 {
+            // 1 bit members end here
+            return Ok(metadata);
+        }
+        metadata.flags = file.u8()?; // it remains unclear, but the ink is not in there
+        metadata.image_bit_depth = file.u8()?;
+        let palette_cast_lib = file.i16()?;
+        let palette_id = file.i16()?;
+        metadata.palette = PaletteRef::from_clut_id(palette_cast_lib, palette_id);
+        Ok(metadata)
+    }
+
+    /// Metadata for pixels that are drawn instead of read from a BITD, like shapes and buttons
+    /// these are 32 bit and registered at their top left corner
+    pub const fn generated(width: i16, height: i16) -> Self {
+        Self {
+            v27: 0x8000,
+            image_pos_y: 0,
+            image_pos_x: 0,
+            image_height: height,
+            image_width: width,
+            _bounding_rect: [0, 0, height, width],
+            image_reg_y: 0,
+            image_reg_x: 0,
+            image_bit_depth: 32,
+            flags: 0,
+            palette: PaletteRef::SystemMac,
+        }
+    }
+
+    /// Bytes per row as stored in the BITD
+    pub const fn pitch(&self) -> usize {
+        (self.v27 & 0x0FFF) as usize
+    }
+
+    pub const fn bit_depth(&self) -> u8 {
+        match self.image_bit_depth {
+            0 => 1,
+            depth => depth,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+    use super::*;
+
+    // a container with the header, the imap, the mmap and one chunk of four bytes
+    fn container<B: ByteOrder>() -> Vec<u8> {
+        let mut data = Vec::new();
+        let u32 = |data: &mut Vec<u8>, value: u32| {
+            let mut bytes = [0; 4];
+            B::write_u32(&mut bytes, value);
+            data.extend_from_slice(&bytes);
+        };
+        // fourccs are stored as a u32 too
+        let fourcc = |data: &mut Vec<u8>, fourcc: &[u8; 4]| {
+            u32(data, u32::from_be_bytes(*fourcc));
+        };
+        let mmap_offset = 28;
+        let test_offset = mmap_offset + 32 + 4 * 20;
+
+        fourcc(&mut data, b"RIFX");
+        u32(&mut data, test_offset + 12 - 8);
+        fourcc(&mut data, b"MV93");
+        fourcc(&mut data, b"imap");
+        u32(&mut data, 8);
+        u32(&mut data, 1);
+        u32(&mut data, mmap_offset);
+
+        fourcc(&mut data, b"mmap");
+        u32(&mut data, 24 + 4 * 20);
+        u32(&mut data, 0x0018_0014);
+        u32(&mut data, 20);
+        u32(&mut data, 4);
+        for _ in 0..3 {
+            u32(&mut data, 0);
+        }
+        for (fourcc_value, length, offset) in [
+            (b"RIFX", test_offset + 12 - 8, 0),
+            (b"imap", 8, 12),
+            (b"mmap", 24 + 4 * 20, mmap_offset),
+            (b"TEST", 4, test_offset),
+        ] {
+            fourcc(&mut data, fourcc_value);
+            u32(&mut data, length);
+            u32(&mut data, offset);
+            u32(&mut data, 0);
+            u32(&mut data, 0);
+        }
+
+        fourcc(&mut data, b"TEST");
+        u32(&mut data, 4);
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        data
+    }
+
+    fn check_container(data: Vec<u8>, endian: Endianness) {
+        let mut director_file = DirectorFile::open("test.dxr", Cursor::new(data)).unwrap();
+        assert_eq!(director_file.endian, endian);
+        assert_eq!(&director_file.header.file_sign, b"MV93");
+        assert_eq!(director_file.header.mmap_offset, 28);
+        assert_eq!(director_file.mmap.amount_of_files, 4);
+        let fourccs: Vec<String> = director_file
+            .sub_files
+            .iter()
+            .map(MacromediaSubFile::fourcc)
+            .collect();
+        assert_eq!(fourccs, ["RIFX", "imap", "mmap", "TEST"]);
+        assert_eq!(director_file.find_chunk("TEST"), Some(3));
+        assert_eq!(director_file.read_chunk(3).unwrap(), [1, 2, 3, 4]);
+        assert!(director_file.members.is_empty());
+    }
+
+    #[test]
+    fn reads_big_endian_container() {
+        check_container(container::<BigEndian>(), Endianness::Big);
+    }
+
+    #[test]
+    fn reads_little_endian_container() {
+        let data = container::<LittleEndian>();
+        assert_eq!(&data[..4], b"XFIR");
+        check_container(data, Endianness::Little);
+    }
+
+    #[test]
+    fn rejects_unknown_magic() {
+        let mut data = container::<BigEndian>();
+        data[..4].copy_from_slice(b"JUNK");
+        assert!(matches!(
+            DirectorFile::open("test.dxr", Cursor::new(data)),
+            Err(AssetParseError::BadMagic { magic, .. }) if &magic == b"JUNK"
+        ));
+    }
+
+    #[test]
+    fn rejects_mmap_outside_the_file() {
+        let mut data = container::<BigEndian>();
+        data[24..28].copy_from_slice(&10_000u32.to_be_bytes());
+        assert!(matches!(
+            DirectorFile::open("test.dxr", Cursor::new(data)),
+            Err(AssetParseError::BadOffset {
+                chunk: MMAP_SLOT,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn reports_chunk_of_wrong_type() {
+        let mut data = container::<BigEndian>();
+        let test_offset = data.len() - 12;
+        data[test_offset..test_offset + 4].copy_from_slice(b"JUNK");
+        let mut director_file = DirectorFile::open("test.dxr", Cursor::new(data)).unwrap();
+        assert!(director_file.read_chunk(3).is_err());
+    }
+}
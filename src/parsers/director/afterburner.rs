@@ -0,0 +1,460 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Cursor, Read, Seek},
+};
+
+use flate2::read::ZlibDecoder;
+use yore::code_pages::CP1252;
+
+use super::{ChunkReader, Endianness};
+
+// Shockwave movies (.dcr) and casts (.cct) are RIFX containers too, but with an FGDM or FGDC codec they are
+// compressed with Afterburner. There is no memory map, instead they have
+//   Fver: the version of the afterburner that wrote the file
+//   Fcdr: the compression types the chunks use, as GUIDs and names
+//   ABMP: a zlib compressed map of every chunk with its slot, offset, sizes and compression type
+//   FGEI: the initial load segment, a zlib compressed blob of the chunks with an offset of -1,
+//         the other chunks follow it at their offset from the start of the segment
+// The slots in the map are the ones the KEY* and CAS* refer to, so the chunks fit the usual chunk model
+// Numbers in these headers are varints, the fourccs and GUIDs follow the container like the mmap does
+
+// chunks stored in the initial load segment have this offset
+const IN_LOAD_SEGMENT: u32 = u32::MAX;
+// the map entry of the initial load segment itself
+const LOAD_SEGMENT_SLOT: u32 = 2;
+
+// GUIDs (data1, data2, data3, data4) of the compression types that have a fixed meaning
+type CompressionId = (u32, u16, u16, [u8; 8]);
+const ZLIB_COMPRESSION: CompressionId = (
+    0xAC99_E904,
+    0x0070,
+    0x0B36,
+    [0x00, 0x00, 0x08, 0x00, 0x07, 0x37, 0x7A, 0x34],
+);
+const NULL_COMPRESSION: CompressionId = (
+    0xAC99_982E,
+    0x005D,
+    0x0D50,
+    [0x00, 0x00, 0x08, 0x00, 0x07, 0x37, 0x7A, 0x34],
+);
+
+pub const fn is_afterburner(codec: &[u8; 4]) -> bool {
+    matches!(codec, b"FGDM" | b"FGDC")
+}
+
+/// A chunk as listed in the ABMP
+struct MapEntry {
+    fourcc: [u8; 4],
+    offset: u32,
+    compressed_length: u32,
+    length: u32,
+    compression: u32,
+}
+
+/// A decompressed chunk, `Err` has the name of a compression that can not be undone yet
+pub struct AfterburnerChunk {
+    pub fourcc: [u8; 4],
+    pub length: u32,
+    pub data: Result<Vec<u8>, String>,
+}
+
+pub struct AfterburnerMovie {
+    pub version: u32,
+    /// Chunks by slot
+    pub chunks: BTreeMap<u32, AfterburnerChunk>,
+}
+
+/// Reads and decompresses every chunk, `reader` has to be right after the codec of the container
+pub fn read_afterburner<R: Read + Seek>(
+    reader: &mut ChunkReader<R>,
+) -> io::Result<AfterburnerMovie> {
+    let endian = reader.endian();
+
+    expect_fourcc(reader, b"Fver")?;
+    let fver_length = reader.varint()?;
+    let fver_start = reader.position()?;
+    let version = reader.varint()?;
+    // newer versions add the imap version, director version and a version string, none of them are needed
+    reader.seek(fver_start + u64::from(fver_length))?;
+
+    expect_fourcc(reader, b"Fcdr")?;
+    let fcdr_length = reader.varint()?;
+    let fcdr = inflate(&reader.bytes(fcdr_length as usize)?)?;
+    let compressions = read_compression_types(&fcdr, endian)?;
+
+    expect_fourcc(reader, b"ABMP")?;
+    let abmp_length = reader.varint()?;
+    let abmp_end = reader.position()? + u64::from(abmp_length);
+    let _abmp_compression = reader.varint()?;
+    let _abmp_uncompressed_length = reader.varint()?;
+    let abmp_compressed_length = abmp_end.saturating_sub(reader.position()?);
+    let abmp = inflate(&reader.bytes(abmp_compressed_length as usize)?)?;
+    let entries = read_map(&abmp, endian)?;
+
+    expect_fourcc(reader, b"FGEI")?;
+    let _fgei_unknown = reader.varint()?;
+    let load_segment_start = reader.position()?;
+
+    let mut chunks = BTreeMap::new();
+
+    let load_segment_entry = entries.get(&LOAD_SEGMENT_SLOT).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the chunk map has no initial load segment",
+        )
+    })?;
+    let load_segment = inflate(&reader.bytes(load_segment_entry.compressed_length as usize)?)?;
+    let mut segment = ChunkReader::new(Cursor::new(load_segment), endian)?;
+    while segment.remaining()? > 0 {
+        let slot = segment.varint()?;
+        let entry = entries.get(&slot).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the initial load segment has chunk {slot} which is not in the map"),
+            )
+        })?;
+        chunks.insert(
+            slot,
+            AfterburnerChunk {
+                fourcc: entry.fourcc,
+                length: entry.length,
+                data: Ok(segment.bytes(entry.compressed_length as usize)?),
+            },
+        );
+    }
+
+    for (slot, entry) in &entries {
+        if *slot == LOAD_SEGMENT_SLOT || entry.offset == IN_LOAD_SEGMENT {
+            continue;
+        }
+        reader.seek(load_segment_start + u64::from(entry.offset))?;
+        let compressed = reader.bytes(entry.compressed_length as usize)?;
+        let data = match compressions.get(entry.compression as usize) {
+            Some((id, _)) if *id == ZLIB_COMPRESSION => Ok(inflate(&compressed)?),
+            Some((id, _)) if *id == NULL_COMPRESSION => Ok(compressed),
+            _ if entry.compressed_length == entry.length => Ok(compressed),
+            Some((_, name)) => Err(name.clone()),
+            None => Err(format!("compression type {}", entry.compression)),
+        };
+        chunks.insert(
+            *slot,
+            AfterburnerChunk {
+                fourcc: entry.fourcc,
+                length: entry.length,
+                data,
+            },
+        );
+    }
+
+    Ok(AfterburnerMovie { version, chunks })
+}
+
+fn expect_fourcc<R: Read + Seek>(
+    reader: &mut ChunkReader<R>,
+    expected: &[u8; 4],
+) -> io::Result<()> {
+    let fourcc = reader.fourcc()?;
+    if &fourcc != expected {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "expected {} but found {}",
+                CP1252.decode(expected),
+                CP1252.decode(&fourcc)
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn inflate(compressed: &[u8]) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    ZlibDecoder::new(compressed).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// The GUID and name of every compression type, chunks refer to them by index
+fn read_compression_types(
+    fcdr: &[u8],
+    endian: Endianness,
+) -> io::Result<Vec<(CompressionId, String)>> {
+    let mut fcdr = ChunkReader::new(Cursor::new(fcdr), endian)?;
+    let count = fcdr.u16()?;
+    let ids = (0..count)
+        .map(|_| {
+            let data1 = fcdr.u32()?;
+            let data2 = fcdr.u16()?;
+            let data3 = fcdr.u16()?;
+            let data4 = fcdr.bytes(8)?;
+            Ok((data1, data2, data3, data4.try_into().unwrap_or_default()))
+        })
+        .collect::<io::Result<Vec<CompressionId>>>()?;
+    ids.into_iter()
+        .map(|id| Ok((id, read_c_string(&mut fcdr)?)))
+        .collect()
+}
+
+fn read_c_string(reader: &mut ChunkReader<Cursor<&[u8]>>) -> io::Result<String> {
+    let mut bytes = Vec::new();
+    loop {
+        match reader.u8()? {
+            0 => return Ok(CP1252.decode(&bytes).to_string()),
+            byte => bytes.push(byte),
+        }
+    }
+}
+
+fn read_map(abmp: &[u8], endian: Endianness) -> io::Result<BTreeMap<u32, MapEntry>> {
+    let mut abmp = ChunkReader::new(Cursor::new(abmp), endian)?;
+    let _unknown1 = abmp.varint()?;
+    let _unknown2 = abmp.varint()?;
+    let count = abmp.varint()?;
+    let mut entries = BTreeMap::new();
+    for _ in 0..count {
+        let slot = abmp.varint()?;
+        let offset = abmp.varint()?;
+        let compressed_length = abmp.varint()?;
+        let length = abmp.varint()?;
+        let compression = abmp.varint()?;
+        let fourcc = abmp.fourcc()?;
+        entries.insert(
+            slot,
+            MapEntry {
+                fourcc,
+                offset,
+                compressed_length,
+                length,
+                compression,
+            },
+        );
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::ZlibEncoder, Compression};
+
+    use super::*;
+
+    const SOUND_COMPRESSION: CompressionId = (0x7204_A889, 0xAF7E, 0x11D1, [0; 8]);
+
+    fn varint(value: u32) -> Vec<u8> {
+        let mut bytes = vec![(value & 0x7F) as u8];
+        let mut rest = value >> 7;
+        while rest > 0 {
+            bytes.insert(0, (rest & 0x7F) as u8 | 0x80);
+            rest >>= 7;
+        }
+        bytes
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn fcdr() -> Vec<u8> {
+        let types = [
+            (ZLIB_COMPRESSION, "zlib"),
+            (NULL_COMPRESSION, "null"),
+            (SOUND_COMPRESSION, "Sound Compression"),
+        ];
+        let mut fcdr = (types.len() as u16).to_be_bytes().to_vec();
+        for ((data1, data2, data3, data4), _) in types {
+            fcdr.extend_from_slice(&data1.to_be_bytes());
+            fcdr.extend_from_slice(&data2.to_be_bytes());
+            fcdr.extend_from_slice(&data3.to_be_bytes());
+            fcdr.extend_from_slice(&data4);
+        }
+        for (_, name) in types {
+            fcdr.extend_from_slice(name.as_bytes());
+            fcdr.push(0);
+        }
+        fcdr
+    }
+
+    // slot, offset, compressed length, length, compression, fourcc
+    type Entry = (u32, u32, u32, u32, u32, [u8; 4]);
+
+    fn abmp(entries: &[Entry]) -> Vec<u8> {
+        let mut abmp = [varint(0), varint(0), varint(entries.len() as u32)].concat();
+        for (slot, offset, compressed_length, length, compression, fourcc) in entries {
+            for value in [slot, offset, compressed_length, length, compression] {
+                abmp.extend(varint(*value));
+            }
+            abmp.extend_from_slice(fourcc);
+        }
+        abmp
+    }
+
+    fn chunk(fourcc: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        [fourcc.as_slice(), &varint(body.len() as u32), body].concat()
+    }
+
+    /// Fver, Fcdr, ABMP and FGEI followed by the load segment and `rest`
+    fn movie(entries: &[Entry], load_segment: &[u8], rest: &[u8]) -> Vec<u8> {
+        let map = abmp(entries);
+        let map_body = [varint(0), varint(map.len() as u32), deflate(&map)].concat();
+        // the version is followed by more that is skipped
+        let fver = [varint(0x501), varint(0x4C1), vec![b'8', b'.', b'5']].concat();
+        [
+            chunk(b"Fver", &fver),
+            chunk(b"Fcdr", &deflate(&fcdr())),
+            chunk(b"ABMP", &map_body),
+            b"FGEI".to_vec(),
+            varint(0),
+            load_segment.to_vec(),
+            rest.to_vec(),
+        ]
+        .concat()
+    }
+
+    fn read(data: Vec<u8>) -> io::Result<AfterburnerMovie> {
+        read_afterburner(&mut ChunkReader::new(Cursor::new(data), Endianness::Big).unwrap())
+    }
+
+    #[test]
+    fn varints_of_the_map() {
+        // chunks in the load segment are at offset -1, which takes five bytes
+        assert_eq!(varint(IN_LOAD_SEGMENT), [0x8F, 0xFF, 0xFF, 0xFF, 0x7F]);
+        let data = [varint(IN_LOAD_SEGMENT), varint(300), varint(0)].concat();
+        let mut reader = ChunkReader::new(Cursor::new(data), Endianness::Little).unwrap();
+        assert_eq!(reader.varint().unwrap(), IN_LOAD_SEGMENT);
+        assert_eq!(reader.varint().unwrap(), 300);
+        assert_eq!(reader.varint().unwrap(), 0);
+        assert!(reader.varint().is_err());
+    }
+
+    #[test]
+    fn compression_types() {
+        let types = read_compression_types(&fcdr(), Endianness::Big).unwrap();
+        assert_eq!(types.len(), 3);
+        assert_eq!(types[0], (ZLIB_COMPRESSION, "zlib".to_string()));
+        assert_eq!(types[1], (NULL_COMPRESSION, "null".to_string()));
+        assert_eq!(
+            types[2],
+            (SOUND_COMPRESSION, "Sound Compression".to_string())
+        );
+        // a name without its terminator
+        let mut cut = fcdr();
+        cut.pop();
+        assert!(read_compression_types(&cut, Endianness::Big).is_err());
+    }
+
+    #[test]
+    fn chunk_map() {
+        let entries = [
+            (2, 0, 10, 20, 0, *b"ILS "),
+            (1, IN_LOAD_SEGMENT, 4, 4, 1, *b"KEY*"),
+            (7, 300, 5, 9, 2, *b"sndS"),
+        ];
+        let map = read_map(&abmp(&entries), Endianness::Big).unwrap();
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 2, 7]);
+        let key = &map[&1];
+        assert_eq!(key.offset, IN_LOAD_SEGMENT);
+        assert_eq!(&key.fourcc, b"KEY*");
+        let sound = &map[&7];
+        assert_eq!(
+            (sound.offset, sound.compressed_length, sound.length),
+            (300, 5, 9)
+        );
+        assert_eq!(sound.compression, 2);
+        assert_eq!(&sound.fourcc, b"sndS");
+
+        // the fourccs follow the byte order of the container, the numbers do not
+        let backwards = read_map(&abmp(&entries), Endianness::Little).unwrap();
+        assert_eq!(&backwards[&1].fourcc, b"*YEK");
+        assert_eq!(backwards[&7].offset, 300);
+
+        let mut cut = abmp(&entries);
+        cut.truncate(cut.len() - 2);
+        assert!(read_map(&cut, Endianness::Big).is_err());
+    }
+
+    #[test]
+    fn synthetic_movie() {
+        let key = b"keys";
+        let config = b"config";
+        let cast = b"cast members";
+        let sound = b"not really a sound";
+        let load_segment = [varint(1), key.to_vec(), varint(3), config.to_vec()].concat();
+        let compressed_load_segment = deflate(&load_segment);
+        let segment_length = compressed_load_segment.len() as u32;
+        let compressed_cast = deflate(cast);
+        let cast_length = compressed_cast.len() as u32;
+        let rest = [
+            compressed_cast,
+            sound.to_vec(),
+            sound.to_vec(),
+            sound.to_vec(),
+        ]
+        .concat();
+        let sound_at = segment_length + cast_length;
+        let sound_length = sound.len() as u32;
+        let entries = [
+            (1, IN_LOAD_SEGMENT, 4, 4, 0, *b"KEY*"),
+            (2, 0, segment_length, load_segment.len() as u32, 0, *b"ILS "),
+            (3, IN_LOAD_SEGMENT, 6, 6, 1, *b"DRCF"),
+            (
+                4,
+                segment_length,
+                cast_length,
+                cast.len() as u32,
+                0,
+                *b"CAS*",
+            ),
+            (5, sound_at, sound_length, sound_length, 1, *b"snd "),
+            // compressed with something else, the lengths tell it apart from stored chunks
+            (6, sound_at + sound_length, sound_length, 40, 2, *b"sndS"),
+            (
+                7,
+                sound_at + 2 * sound_length,
+                sound_length,
+                40,
+                9,
+                *b"sndS",
+            ),
+        ];
+        let movie = read(movie(&entries, &compressed_load_segment, &rest)).unwrap();
+        assert_eq!(movie.version, 0x501);
+        // the load segment itself is not a chunk
+        assert_eq!(
+            movie.chunks.keys().copied().collect::<Vec<_>>(),
+            [1, 3, 4, 5, 6, 7]
+        );
+        let data = |slot: u32| movie.chunks[&slot].data.clone();
+        assert_eq!(data(1).unwrap(), key);
+        assert_eq!(&movie.chunks[&3].fourcc, b"DRCF");
+        assert_eq!(data(3).unwrap(), config);
+        assert_eq!(data(4).unwrap(), cast);
+        assert_eq!(movie.chunks[&4].length, cast.len() as u32);
+        assert_eq!(data(5).unwrap(), sound);
+        assert_eq!(data(6).unwrap_err(), "Sound Compression");
+        assert_eq!(data(7).unwrap_err(), "compression type 9");
+    }
+
+    #[test]
+    fn broken_movies() {
+        let segment = deflate(&[varint(1), b"keys".to_vec()].concat());
+        let length = segment.len() as u32;
+        let key = (1, IN_LOAD_SEGMENT, 4, 4, 0, *b"KEY*");
+
+        let without_segment = movie(&[key], &segment, &[]);
+        assert!(read(without_segment).is_err());
+
+        // slot 1 is in the segment but not in the map
+        let unknown_slot = movie(&[(2, 0, length, 5, 0, *b"ILS ")], &segment, &[]);
+        assert!(read(unknown_slot).is_err());
+
+        let mut wrong_start = movie(&[key, (2, 0, length, 5, 0, *b"ILS ")], &segment, &[]);
+        wrong_start[..4].copy_from_slice(b"Fcdr");
+        assert_eq!(
+            read(wrong_start).err().unwrap().to_string(),
+            "expected Fver but found Fcdr"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::VecDeque;
+
+use super::{ink::Transparency, palette::MacromediaPalette, MacromediaCastBitmapMetadata};
+
+// BITD chunks hold the pixels of a bitmap member, rows are `pitch` bytes long and padded to an even length
+// when the chunk is smaller than pitch * height it is packbits compressed
+// 16 and 32 bit rows are split per channel: all the high bytes of a row come before all the low bytes
+
+/// Decodes a BITD chunk to RGBA, indexed bitmaps are looked up in `palette`, which should fit the bit depth
+/// `transparency` decides which of the white pixels become transparent
+pub fn decode_bitd(
+    bitmap_meta: &MacromediaCastBitmapMetadata,
+    data: &[u8],
+    palette: &MacromediaPalette,
+    transparency: Transparency,
+) -> Vec<u8> {
+    let width = bitmap_meta.image_width.max(0) as usize;
+    let height = bitmap_meta.image_height.max(0) as usize;
+    let bit_depth = bitmap_meta.bit_depth();
+    let pitch = row_length(bitmap_meta, width);
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    let pixels = if data.len() >= pitch * height {
+        data[..pitch * height].to_vec()
+    } else {
+        unpack_bits(data, pitch * height)
+    };
+
+    let mut rgba_data = Vec::<u8>::with_capacity(width * height * 4);
+    let mut white = Vec::<bool>::with_capacity(width * height);
+    for row in pixels.chunks_exact(pitch).take(height) {
+        for x in 0..width {
+            let ([r, g, b], is_white) = match bit_depth {
+                1 => indexed(palette, (row[x / 8] >> (7 - x % 8)) & 0b1),
+                2 => indexed(palette, (row[x / 4] >> (6 - 2 * (x % 4))) & 0b11),
+                4 => indexed(palette, (row[x / 2] >> (4 - 4 * (x % 2))) & 0b1111),
+                16 => {
+                    let plane = pitch / 2;
+                    let color = u16::from_be_bytes([row[x], row[plane + x]]);
+                    let channel = |shift: u16| {
+                        let value = ((color >> shift) & 0x1F) as u8;
+                        (value << 3) | (value >> 2)
+                    };
+                    direct([channel(10), channel(5), channel(0)])
+                }
+                32 => {
+                    // the alpha plane comes first, director only uses it when asked to
+                    let plane = pitch / 4;
+                    direct([row[plane + x], row[2 * plane + x], row[3 * plane + x]])
+                }
+                _ => indexed(palette, row[x]),
+            };
+            rgba_data.extend_from_slice(&[r, g, b, 0xFF]);
+            white.push(is_white);
+        }
+    }
+
+    let transparent = match transparency {
+        Transparency::Opaque => vec![false; white.len()],
+        Transparency::White => white,
+        Transparency::Matte => matte(&white, width),
+    };
+    for (pixel, transparent) in rgba_data.chunks_exact_mut(4).zip(transparent) {
+        if transparent {
+            pixel[3] = 0x00;
+        }
+    }
+    // a short chunk leaves the bottom of the image transparent instead of failing the whole bitmap
+    rgba_data.resize(width * height * 4, 0);
+    rgba_data
+}
+
+/// The white pixels that can be reached from the edge of the bitmap without crossing another colour
+fn matte(white: &[bool], width: usize) -> Vec<bool> {
+    let height = white.len() / width;
+    let mut outside = vec![false; white.len()];
+    let mut queue = VecDeque::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            if (x == 0 || y == 0 || x == width - 1 || y == height - 1) && white[y * width + x] {
+                outside[y * width + x] = true;
+                queue.push_back((x, y));
+            }
+        }
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbours {
+            if nx < width && ny < height && white[ny * width + nx] && !outside[ny * width + nx] {
+                outside[ny * width + nx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    outside
+}
+
+/// The length of a decoded row, the member header knows it but 1 bit members may leave it out
+fn row_length(bitmap_meta: &MacromediaCastBitmapMetadata, width: usize) -> usize {
+    let bits = width * bitmap_meta.bit_depth() as usize;
+    (bits.div_ceil(16) * 2).max(bitmap_meta.pitch())
+}
+
+/// Packbits: a byte below 0x80 copies that many + 1 bytes, anything else repeats the next byte 257 - n times
+fn unpack_bits(data: &[u8], length: usize) -> Vec<u8> {
+    let mut pixels = Vec::<u8>::with_capacity(length);
+    let mut position = 0;
+
+    while position < data.len() && pixels.len() < length {
+        let control = data[position] as usize;
+        position += 1;
+        if control < 0x80 {
+            let end = (position + control + 1).min(data.len());
+            pixels.extend_from_slice(&data[position..end]);
+            position = end;
+        } else if let Some(&value) = data.get(position) {
+            position += 1;
+            pixels.resize(pixels.len() + 0x101 - control, value);
+        }
+    }
+    pixels.resize(length, 0);
+    pixels
+}
+
+// white is the colour and not an index, index 0 is white in the mac palettes but black in the windows ones
+fn indexed(palette: &MacromediaPalette, index: u8) -> ([u8; 3], bool) {
+    direct(palette.color(index))
+}
+
+fn direct(rgb: [u8; 3]) -> ([u8; 3], bool) {
+    (rgb, rgb == [0xFF; 3])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::parsers::director::palette::{SYSTEM_MAC, SYSTEM_WIN};
+
+    // an 8 bit member of 4 by 1 pixels with the windows system palette
+    fn metadata() -> MacromediaCastBitmapMetadata {
+        let mut specific_data = vec![0x80, 4, 0, 0, 0, 0, 0, 1, 0, 4];
+        specific_data.extend_from_slice(&[0; 12]); // bounding rect and registration point
+        specific_data.extend_from_slice(&[0, 8, 0, 0, 0xFF, 0x9B]);
+        MacromediaCastBitmapMetadata::read(&specific_data).unwrap()
+    }
+
+    fn alpha(rgba: &[u8]) -> Vec<u8> {
+        rgba.chunks_exact(4).map(|pixel| pixel[3]).collect()
+    }
+
+    #[test]
+    fn white_follows_the_palette() {
+        let metadata = metadata();
+        assert_eq!(metadata.bit_depth(), 8);
+        let pixels = [0, 255, 0, 255];
+
+        // windows: index 0 is black and 255 is white
+        let rgba = decode_bitd(&metadata, &pixels, &SYSTEM_WIN, Transparency::White);
+        assert_eq!(rgba[..4], [0, 0, 0, 0xFF]);
+        assert_eq!(rgba[4..8], [0xFF, 0xFF, 0xFF, 0]);
+        assert_eq!(alpha(&rgba), [0xFF, 0, 0xFF, 0]);
+
+        // mac: the other way around
+        let rgba = decode_bitd(&metadata, &pixels, &SYSTEM_MAC, Transparency::White);
+        assert_eq!(alpha(&rgba), [0, 0xFF, 0, 0xFF]);
+
+        let rgba = decode_bitd(&metadata, &pixels, &SYSTEM_WIN, Transparency::Opaque);
+        assert_eq!(alpha(&rgba), [0xFF; 4]);
+    }
+
+    #[test]
+    fn matte_keeps_enclosed_white() {
+        let white = [
+            true, true, true, //
+            true, false, true, //
+            false, true, false, //
+        ];
+        // the white in the middle of the bottom row touches the edge
+        assert_eq!(matte(&white, 3), white);
+        let white = [
+            false, false, false, //
+            false, true, false, //
+            false, false, false, //
+        ];
+        assert_eq!(matte(&white, 3), [false; 9]);
+    }
+
+    #[test]
+    fn unpacks_literal_and_repeated_runs() {
+        // 3 bytes as they are, then 9 repeated 257 - 0xFE = 3 times
+        let packed = [0x02, 1, 2, 3, 0xFE, 9];
+        assert_eq!(unpack_bits(&packed, 6), [1, 2, 3, 9, 9, 9]);
+    }
+
+    #[test]
+    fn unpacks_to_the_asked_length() {
+        let packed = [0x02, 1, 2, 3, 0xFE, 9];
+        assert_eq!(unpack_bits(&packed, 4), [1, 2, 3, 9]);
+        // missing pixels are filled with 0
+        assert_eq!(unpack_bits(&packed, 8), [1, 2, 3, 9, 9, 9, 0, 0]);
+    }
+
+    #[test]
+    fn unpacks_cut_off_data() {
+        // the literal run asks for 4 bytes but only 2 are left, the repeat has no value
+        assert_eq!(unpack_bits(&[0x03, 1, 2], 4), [1, 2, 0, 0]);
+        assert_eq!(unpack_bits(&[0xFF], 2), [0, 0]);
+    }
+}
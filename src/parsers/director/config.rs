@@ -0,0 +1,158 @@
+use std::io::{self, Cursor};
+
+use super::{palette::PaletteRef, ChunkReader, Endianness};
+
+// Every movie and cast has one config chunk, VWCF up to director 5 and DRCF after that, the layout is the same
+// It is big endian like the other chunks, most of its fields are settings of the authoring tool
+
+// the director version is stored as a build number, these are the first builds of every release
+const VERSIONS: &[(u16, u16)] = &[
+    (1951, 1200),
+    (1923, 1150),
+    (1922, 1100),
+    (1851, 1000),
+    (1700, 850),
+    (1410, 800),
+    (1224, 700),
+    (1218, 600),
+    (1201, 500),
+    (1117, 404),
+    (1115, 400),
+    (1029, 310),
+    (1028, 300),
+];
+
+/// The platform the movie was saved on, it decides which system palette is the default
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoviePlatform {
+    Mac,
+    Windows,
+    Unknown(i16),
+}
+
+/// The settings of a movie that apply to all of its frames
+#[derive(Clone, Debug)]
+pub struct MovieConfig {
+    /// The build number of director that saved the movie, see `director_version` for the release
+    pub version: u16,
+    pub stage_top: i16,
+    pub stage_left: i16,
+    pub stage_bottom: i16,
+    pub stage_right: i16,
+    /// Frames per second when the score does not set a tempo, 0 when the movie has none
+    pub tempo: u8,
+    pub default_palette: Option<PaletteRef>,
+    pub platform: MoviePlatform,
+}
+
+impl MovieConfig {
+    /// The release of director that saved the movie times 100, so director 6 is 600 and 8.5 is 850
+    pub fn director_version(&self) -> u16 {
+        VERSIONS
+            .iter()
+            .find(|(build, _)| self.version >= *build)
+            .map_or(200, |(_, release)| *release)
+    }
+
+    pub const fn stage_width(&self) -> u16 {
+        self.stage_right.abs_diff(self.stage_left)
+    }
+
+    pub const fn stage_height(&self) -> u16 {
+        self.stage_bottom.abs_diff(self.stage_top)
+    }
+}
+
+pub fn read_config(data: &[u8]) -> io::Result<MovieConfig> {
+    let mut config = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+    config.skip(4)?; // length and file version
+    let stage_top = config.i16()?;
+    let stage_left = config.i16()?;
+    let stage_bottom = config.i16()?;
+    let stage_right = config.i16()?;
+
+    config.seek(36)?; // members, stage colour, comment font and bit depth
+    let version = config.u16()?;
+
+    config.seek(54)?;
+    let frame_rate = config.i16()?;
+    let platform = match config.i16()? {
+        1 => MoviePlatform::Mac,
+        2 => MoviePlatform::Windows,
+        other => MoviePlatform::Unknown(other),
+    };
+
+    let mut movie_config = MovieConfig {
+        version,
+        stage_top,
+        stage_left,
+        stage_bottom,
+        stage_right,
+        tempo: u8::try_from(frame_rate).unwrap_or(0),
+        default_palette: None,
+        platform,
+    };
+
+    // protection and a checksum come before the palette, which only got a cast library in director 5
+    config.seek(68)?;
+    let palette_cast_lib = config.i16()?;
+    let palette = config.i16()?;
+    movie_config.default_palette = if movie_config.director_version() < 500 {
+        PaletteRef::from_score_id(0, palette)
+    } else {
+        PaletteRef::from_score_id(palette_cast_lib, palette)
+    };
+    Ok(movie_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(version: u16) -> MovieConfig {
+        MovieConfig {
+            version,
+            stage_top: 0,
+            stage_left: 0,
+            stage_bottom: 480,
+            stage_right: 640,
+            tempo: 0,
+            default_palette: None,
+            platform: MoviePlatform::Windows,
+        }
+    }
+
+    #[test]
+    fn director_version_table() {
+        let builds = [
+            (0, 200),
+            (1027, 200),
+            (1028, 300),
+            (1029, 310),
+            (1115, 400),
+            (1117, 404),
+            (1201, 500),
+            (1217, 500),
+            (1218, 600),
+            (1224, 700),
+            (1410, 800),
+            (1700, 850),
+            (1851, 1000),
+            (1921, 1000),
+            (1922, 1100),
+            (1923, 1150),
+            (1950, 1150),
+            (1951, 1200),
+            (u16::MAX, 1200),
+        ];
+        for (build, release) in builds {
+            assert_eq!(config(build).director_version(), release, "build {build}");
+        }
+    }
+
+    #[test]
+    fn stage_size() {
+        assert_eq!(config(1218).stage_width(), 640);
+        assert_eq!(config(1218).stage_height(), 480);
+    }
+}
@@ -0,0 +1,111 @@
+use std::io;
+
+use yore::code_pages::CP1252;
+
+/// Everything that can go wrong while reading a director file, every error knows which file and which chunk slot it came from
+pub enum AssetParseError {
+    MissingFile {
+        file: String,
+    },
+    BadMagic {
+        file: String,
+        magic: [u8; 4],
+    },
+    TruncatedChunk {
+        file: String,
+        chunk: u32,
+        source: io::Error,
+    },
+    UnknownMemberType {
+        file: String,
+        chunk: u32,
+        member_type: u32,
+    },
+    BadOffset {
+        file: String,
+        chunk: u32,
+        offset: u64,
+    },
+    UnsupportedFormat {
+        file: String,
+        chunk: u32,
+        format: String,
+    },
+}
+
+impl AssetParseError {
+    /// Reads that run past the end of a chunk end up here, the chunk was shorter than its fields claim
+    pub fn truncated(file: &str, chunk: u32) -> impl Fn(io::Error) -> Self + '_ {
+        move |source| Self::TruncatedChunk {
+            file: file.to_owned(),
+            chunk,
+            source,
+        }
+    }
+
+    /// Like `truncated`, but decoders also report formats they can not handle yet
+    pub fn decode_failed(file: &str, chunk: u32) -> impl Fn(io::Error) -> Self + '_ {
+        move |source| {
+            if source.kind() == io::ErrorKind::Unsupported {
+                Self::UnsupportedFormat {
+                    file: file.to_owned(),
+                    chunk,
+                    format: source.to_string(),
+                }
+            } else {
+                Self::truncated(file, chunk)(source)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for AssetParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingFile { file } => write!(f, "{file}: file not found"),
+            Self::BadMagic { file, magic } => write!(
+                f,
+                "{file}: not a shockwave file, found magic {:?}",
+                CP1252.decode(magic)
+            ),
+            Self::TruncatedChunk {
+                file,
+                chunk,
+                source,
+            } => write!(f, "{file}: chunk {chunk} is truncated ({source})"),
+            Self::UnknownMemberType {
+                file,
+                chunk,
+                member_type,
+            } => write!(
+                f,
+                "{file}: chunk {chunk} is a cast member of unknown type {member_type}"
+            ),
+            Self::BadOffset {
+                file,
+                chunk,
+                offset,
+            } => write!(f, "{file}: chunk {chunk} has a bad offset {offset}"),
+            Self::UnsupportedFormat {
+                file,
+                chunk,
+                format,
+            } => write!(f, "{file}: chunk {chunk} uses unsupported {format}"),
+        }
+    }
+}
+
+impl std::fmt::Debug for AssetParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl std::error::Error for AssetParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::TruncatedChunk { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
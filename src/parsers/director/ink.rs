@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+/// How a sprite is drawn onto the stage, stored in the low 6 bits of the ink byte of a sprite channel
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Ink {
+    Copy,
+    Transparent,
+    Reverse,
+    Ghost,
+    NotCopy,
+    NotTransparent,
+    NotReverse,
+    NotGhost,
+    Matte,
+    Mask,
+    Blend,
+    AddPin,
+    Add,
+    SubtractPin,
+    BackgroundTransparent,
+    Lightest,
+    Subtract,
+    Darkest,
+    Lighten,
+    Darken,
+    Unknown(u8),
+}
+
+impl Ink {
+    pub const fn from_ink_data(ink_data: u8) -> Self {
+        match ink_data & 0x3F {
+            0 => Self::Copy,
+            1 => Self::Transparent,
+            2 => Self::Reverse,
+            3 => Self::Ghost,
+            4 => Self::NotCopy,
+            5 => Self::NotTransparent,
+            6 => Self::NotReverse,
+            7 => Self::NotGhost,
+            8 => Self::Matte,
+            9 => Self::Mask,
+            32 => Self::Blend,
+            33 => Self::AddPin,
+            34 => Self::Add,
+            35 => Self::SubtractPin,
+            36 => Self::BackgroundTransparent,
+            37 => Self::Lightest,
+            38 => Self::Subtract,
+            39 => Self::Darkest,
+            40 => Self::Lighten,
+            41 => Self::Darken,
+            ink => Self::Unknown(ink),
+        }
+    }
+
+    /// What the ink does to the white of a bitmap, `None` for inks that do not hide it
+    pub const fn transparency(self) -> Option<Transparency> {
+        match self {
+            Self::Copy | Self::NotCopy => Some(Transparency::Opaque),
+            Self::Matte | Self::Mask => Some(Transparency::Matte),
+            Self::Transparent | Self::BackgroundTransparent | Self::Darkest => {
+                Some(Transparency::White)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Which white pixels of a bitmap end up transparent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transparency {
+    Opaque,
+    /// Every white pixel
+    White,
+    /// Only the white that touches the edge of the bitmap, like cutting it out with scissors
+    Matte,
+}
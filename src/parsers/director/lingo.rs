@@ -0,0 +1,769 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    io::{self, Cursor},
+};
+
+use yore::code_pages::CP1252;
+
+use super::{ChunkReader, Endianness};
+
+// Compiled lingo is spread over three kinds of chunks, all of them big endian
+// the script context (Lctx or LctX) lists the Lscr chunks of a cast and points at the Lnam chunk
+// the Lnam chunk holds every name (handlers, variables, symbols) the scripts refer to by number
+// every Lscr chunk is one script: its handlers with their bytecode, properties, globals and literals
+// Handlers written for director 8.5 and up carry an extra stack height field, which makes their records longer
+
+const HANDLER_SIZE: u64 = 42;
+const HANDLER_SIZE_D85: u64 = 46;
+
+const LITERAL_STRING: u32 = 1;
+const LITERAL_INT: u32 = 4;
+const LITERAL_FLOAT: u32 = 9;
+
+/// The scripts of one cast and the names they share
+#[derive(Clone, Debug)]
+pub struct LingoContext {
+    pub names: Vec<String>,
+    /// Keyed by script number, which the info of a cast member refers to
+    pub scripts: HashMap<u32, LingoScript>,
+    /// Local variables and literals are numbered in steps of this
+    variable_multiplier: i32,
+}
+
+#[derive(Clone, Debug)]
+pub struct LingoScript {
+    pub cast_member: i32,
+    pub flags: u32,
+    pub properties: Vec<String>,
+    pub globals: Vec<String>,
+    pub handlers: Vec<LingoHandler>,
+    pub literals: Vec<LingoLiteral>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LingoHandler {
+    pub name: String,
+    pub arguments: Vec<String>,
+    pub locals: Vec<String>,
+    pub globals: Vec<String>,
+    pub instructions: Vec<LingoInstruction>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LingoInstruction {
+    /// Offset of the instruction in the bytecode of the handler, jumps are relative to it
+    pub position: u32,
+    pub opcode: LingoOpcode,
+    pub operand: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LingoLiteral {
+    String(String),
+    Int(i32),
+    Float(f64),
+    Unknown(u32),
+}
+
+/// Where the names and scripts of a script context are stored
+pub struct LingoContextMap {
+    pub names_slot: Option<u32>,
+    /// Script number and the slot of its Lscr chunk
+    pub script_slots: Vec<(u32, u32)>,
+}
+
+/// Reads a script context (Lctx or LctX) chunk
+pub fn read_context_map(data: &[u8]) -> io::Result<LingoContextMap> {
+    let mut lctx = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+    lctx.skip(8)?;
+    let entry_count = lctx.u32()?;
+    let _entry_count2 = lctx.u32()?;
+    let entries_offset = lctx.u16()?;
+    lctx.skip(2 + 4 + 4 + 4)?;
+    let names_slot = lctx.i32()?;
+
+    lctx.seek(u64::from(entries_offset))?;
+    let mut script_slots = Vec::new();
+    for number in 1..=entry_count {
+        let _unknown = lctx.i32()?;
+        let slot = lctx.i32()?;
+        lctx.skip(4)?;
+        if slot > 0 {
+            // a free entry otherwise
+            script_slots.push((number, slot as u32));
+        }
+    }
+
+    Ok(LingoContextMap {
+        names_slot: u32::try_from(names_slot).ok().filter(|slot| *slot > 0),
+        script_slots,
+    })
+}
+
+/// Reads an Lnam chunk
+pub fn read_names(data: &[u8]) -> io::Result<Vec<String>> {
+    let mut lnam = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+    lnam.skip(16)?;
+    let names_offset = lnam.u16()?;
+    let names_count = lnam.u16()?;
+
+    lnam.seek(u64::from(names_offset))?;
+    (0..names_count).map(|_| lnam.pascal_string()).collect()
+}
+
+/// Reads an Lscr chunk, the names are the ones of the context it belongs to
+/// `director_version` is the release from the movie config (600 for director 6)
+pub fn read_script(
+    data: &[u8],
+    names: &[String],
+    director_version: u16,
+) -> io::Result<LingoScript> {
+    let mut lscr = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+    lscr.seek(38)?;
+    let flags = lscr.u32()?;
+    let _unknown = lscr.i16()?;
+    let cast_member = lscr.i32()?;
+    let _factory_name = lscr.i16()?;
+    lscr.skip(2 + 4 + 4)?; // handler vectors
+    let properties_count = lscr.u16()?;
+    let properties_offset = lscr.u32()?;
+    let globals_count = lscr.u16()?;
+    let globals_offset = lscr.u32()?;
+    let handlers_count = lscr.u16()?;
+    let handlers_offset = lscr.u32()?;
+    let literals_count = lscr.u16()?;
+    let literals_offset = lscr.u32()?;
+    let _literals_data_count = lscr.u32()?;
+    let literals_data_offset = lscr.u32()?;
+
+    let properties = read_name_table(&mut lscr, properties_count, properties_offset, names)?;
+    let globals = read_name_table(&mut lscr, globals_count, globals_offset, names)?;
+
+    let handler_size = if director_version >= 850 {
+        HANDLER_SIZE_D85
+    } else {
+        HANDLER_SIZE
+    };
+    let mut handlers = Vec::new();
+    for index in 0..u64::from(handlers_count) {
+        lscr.seek(u64::from(handlers_offset) + index * handler_size)?;
+        handlers.push(read_handler(&mut lscr, names)?);
+    }
+
+    let mut literals = Vec::new();
+    for index in 0..u64::from(literals_count) {
+        lscr.seek(u64::from(literals_offset) + index * 8)?;
+        let literal_type = lscr.u32()?;
+        let offset = lscr.u32()?;
+        let literal = match literal_type {
+            LITERAL_INT => LingoLiteral::Int(offset as i32), // small enough to live in the offset
+            LITERAL_STRING => {
+                lscr.seek(u64::from(literals_data_offset) + u64::from(offset))?;
+                let length = lscr.u32()? as usize;
+                let text = lscr.bytes(length)?;
+                // the length counts the closing null
+                LingoLiteral::String(
+                    CP1252
+                        .decode(text.strip_suffix(&[0]).unwrap_or(&text))
+                        .to_string(),
+                )
+            }
+            LITERAL_FLOAT => {
+                lscr.seek(u64::from(literals_data_offset) + u64::from(offset))?;
+                let length = lscr.u32()? as usize;
+                LingoLiteral::Float(read_float(&lscr.bytes(length)?))
+            }
+            other => LingoLiteral::Unknown(other),
+        };
+        literals.push(literal);
+    }
+
+    Ok(LingoScript {
+        cast_member,
+        flags,
+        properties,
+        globals,
+        handlers,
+        literals,
+    })
+}
+
+fn read_handler(
+    lscr: &mut ChunkReader<Cursor<&[u8]>>,
+    names: &[String],
+) -> io::Result<LingoHandler> {
+    let name_id = lscr.i16()?;
+    let _vector_position = lscr.u16()?;
+    let compiled_length = lscr.u32()?;
+    let compiled_offset = lscr.u32()?;
+    let arguments_count = lscr.u16()?;
+    let arguments_offset = lscr.u32()?;
+    let locals_count = lscr.u16()?;
+    let locals_offset = lscr.u32()?;
+    let globals_count = lscr.u16()?;
+    let globals_offset = lscr.u32()?;
+
+    let arguments = read_name_table(lscr, arguments_count, arguments_offset, names)?;
+    let locals = read_name_table(lscr, locals_count, locals_offset, names)?;
+    let globals = read_name_table(lscr, globals_count, globals_offset, names)?;
+
+    let bytecode = lscr
+        .sub_reader(u64::from(compiled_offset), u64::from(compiled_length))?
+        .bytes(compiled_length as usize)?;
+
+    Ok(LingoHandler {
+        name: name(names, i32::from(name_id)),
+        arguments,
+        locals,
+        globals,
+        instructions: read_bytecode(&bytecode)?,
+    })
+}
+
+fn read_name_table(
+    lscr: &mut ChunkReader<Cursor<&[u8]>>,
+    count: u16,
+    offset: u32,
+    names: &[String],
+) -> io::Result<Vec<String>> {
+    lscr.seek(u64::from(offset))?;
+    (0..count)
+        .map(|_| Ok(name(names, i32::from(lscr.i16()?))))
+        .collect()
+}
+
+fn read_bytecode(bytecode: &[u8]) -> io::Result<Vec<LingoInstruction>> {
+    let mut reader = ChunkReader::new(Cursor::new(bytecode), Endianness::Big)?;
+    let mut instructions = Vec::new();
+
+    while reader.remaining()? > 0 {
+        let position = reader.position()? as u32;
+        let byte = reader.u8()?;
+        let opcode = LingoOpcode::from_byte(byte);
+        // the two high bits tell how wide the operand is, the push of an int is the only signed one
+        let operand = match byte {
+            0xC0.. => reader.i32()?,
+            0x80.. if opcode == LingoOpcode::PushInt16 || opcode == LingoOpcode::PushInt8 => {
+                i32::from(reader.i16()?)
+            }
+            0x80.. => i32::from(reader.u16()?),
+            0x40.. if opcode == LingoOpcode::PushInt8 => i32::from(reader.i8()?),
+            0x40.. => i32::from(reader.u8()?),
+            _ => 0,
+        };
+        instructions.push(LingoInstruction {
+            position,
+            opcode,
+            operand,
+        });
+    }
+    Ok(instructions)
+}
+
+// literals are either a double or an 80 bit extended float from the 68k days
+fn read_float(data: &[u8]) -> f64 {
+    match data.len() {
+        8 => f64::from_be_bytes(data.try_into().unwrap_or_default()),
+        10 => {
+            let sign = if data[0] & 0x80 == 0 { 1. } else { -1. };
+            let exponent = i32::from(u16::from_be_bytes([data[0] & 0x7F, data[1]]));
+            let mantissa = u64::from_be_bytes(data[2..10].try_into().unwrap_or_default());
+            if exponent == 0 && mantissa == 0 {
+                return 0.;
+            }
+            sign * mantissa as f64 * 2f64.powi(exponent - 16383 - 63)
+        }
+        _ => 0.,
+    }
+}
+
+fn name(names: &[String], id: i32) -> String {
+    usize::try_from(id)
+        .ok()
+        .and_then(|id| names.get(id))
+        .cloned()
+        .unwrap_or_else(|| format!("name_{id}"))
+}
+
+impl LingoContext {
+    /// `capital_x` tells if the context chunk was called LctX, which changes how variables are numbered,
+    /// `director_version` is the release from the movie config or 0 when the movie has none
+    pub const fn new(
+        names: Vec<String>,
+        scripts: HashMap<u32, LingoScript>,
+        capital_x: bool,
+        director_version: u16,
+    ) -> Self {
+        Self {
+            names,
+            scripts,
+            // director 4 numbers them in sixes, 5 and up in eights and LctX files just count
+            variable_multiplier: match (capital_x, director_version) {
+                (true, _) => 1,
+                (false, 1..500) => 6,
+                (false, _) => 8,
+            },
+        }
+    }
+
+    /// The name behind a name id of an instruction
+    pub fn name(&self, id: i32) -> String {
+        name(&self.names, id)
+    }
+
+    /// Index of the local, argument or literal an instruction refers to
+    pub const fn variable_index(&self, operand: i32) -> usize {
+        (operand / self.variable_multiplier) as usize
+    }
+
+    /// A readable listing of every handler of the script, one instruction per line
+    pub fn disassemble(&self, script: &LingoScript) -> String {
+        let mut listing = String::new();
+        for property in &script.properties {
+            _ = writeln!(listing, "property {property}");
+        }
+        for global in &script.globals {
+            _ = writeln!(listing, "global {global}");
+        }
+        for handler in &script.handlers {
+            _ = writeln!(
+                listing,
+                "on {} {}",
+                handler.name,
+                handler.arguments.join(", ")
+            );
+            if !handler.globals.is_empty() {
+                _ = writeln!(listing, "  global {}", handler.globals.join(", "));
+            }
+            for instruction in &handler.instructions {
+                _ = write!(
+                    listing,
+                    "  [{:4}] {:<20}",
+                    instruction.position,
+                    instruction.opcode.name()
+                );
+                if instruction.opcode.has_operand() {
+                    _ = write!(listing, " {}", instruction.operand);
+                }
+                if let Some(comment) = self.comment(script, handler, instruction) {
+                    _ = write!(listing, " -- {comment}");
+                }
+                listing.push('\n');
+            }
+            listing.push_str("end\n\n");
+        }
+        listing
+    }
+
+    fn comment(
+        &self,
+        script: &LingoScript,
+        handler: &LingoHandler,
+        instruction: &LingoInstruction,
+    ) -> Option<String> {
+        let operand = instruction.operand;
+        let variable = |list: &[String]| list.get(self.variable_index(operand)).cloned();
+        match instruction.opcode {
+            LingoOpcode::PushSymbol
+            | LingoOpcode::PushVarRef
+            | LingoOpcode::PushChunkVarRef
+            | LingoOpcode::GetGlobal
+            | LingoOpcode::GetGlobal2
+            | LingoOpcode::SetGlobal
+            | LingoOpcode::SetGlobal2
+            | LingoOpcode::GetProperty
+            | LingoOpcode::SetProperty
+            | LingoOpcode::ExternalCall
+            | LingoOpcode::ObjectCall
+            | LingoOpcode::ObjectCallOldStyle
+            | LingoOpcode::TellCall
+            | LingoOpcode::GetMovieProperty
+            | LingoOpcode::SetMovieProperty
+            | LingoOpcode::GetObjectProperty
+            | LingoOpcode::SetObjectProperty
+            | LingoOpcode::GetChainedProperty
+            | LingoOpcode::GetTopLevelProperty
+            | LingoOpcode::TheBuiltin
+            | LingoOpcode::NewObject => Some(self.name(operand)),
+            LingoOpcode::GetParameter | LingoOpcode::SetParameter => variable(&handler.arguments),
+            LingoOpcode::GetLocal | LingoOpcode::SetLocal => variable(&handler.locals),
+            LingoOpcode::PushConstant => script
+                .literals
+                .get(self.variable_index(operand))
+                .map(|literal| format!("{literal:?}")),
+            LingoOpcode::LocalCall => script
+                .handlers
+                .get(operand as usize)
+                .map(|called| called.name.clone()),
+            LingoOpcode::Jump | LingoOpcode::JumpIfZero => {
+                Some(format!("to {}", instruction.position as i32 + operand))
+            }
+            LingoOpcode::EndRepeat => Some(format!("to {}", instruction.position as i32 - operand)),
+            _ => None,
+        }
+    }
+}
+
+/// Lingo bytecode operations, single byte operations are below 0x40
+/// the others are stored as 0x40 + n with 0x40 added once or twice more for a wider operand
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LingoOpcode {
+    Return,
+    ReturnFactory,
+    PushZero,
+    Multiply,
+    Add,
+    Subtract,
+    Divide,
+    Modulo,
+    Negate,
+    JoinString,
+    JoinPaddedString,
+    LessThan,
+    LessThanOrEqual,
+    NotEqual,
+    Equal,
+    GreaterThan,
+    GreaterThanOrEqual,
+    And,
+    Or,
+    Not,
+    ContainsString,
+    StartsWithString,
+    GetChunk,
+    HiliteChunk,
+    OntoSprite,
+    IntoSprite,
+    GetField,
+    StartTell,
+    EndTell,
+    PushList,
+    PushPropertyList,
+    Swap,
+    PushInt8,
+    PushArgListNoReturn,
+    PushArgList,
+    PushConstant,
+    PushSymbol,
+    PushVarRef,
+    GetGlobal2,
+    GetGlobal,
+    GetProperty,
+    GetParameter,
+    GetLocal,
+    SetGlobal2,
+    SetGlobal,
+    SetProperty,
+    SetParameter,
+    SetLocal,
+    Jump,
+    EndRepeat,
+    JumpIfZero,
+    LocalCall,
+    ExternalCall,
+    ObjectCallOldStyle,
+    Put,
+    PutChunk,
+    DeleteChunk,
+    Get,
+    Set,
+    GetMovieProperty,
+    SetMovieProperty,
+    GetObjectProperty,
+    SetObjectProperty,
+    TellCall,
+    Peek,
+    Pop,
+    TheBuiltin,
+    ObjectCall,
+    PushChunkVarRef,
+    PushInt16,
+    PushInt32,
+    GetChainedProperty,
+    PushFloat32,
+    GetTopLevelProperty,
+    NewObject,
+    Unknown(u8),
+}
+
+impl LingoOpcode {
+    pub const fn from_byte(byte: u8) -> Self {
+        let opcode = if byte >= 0x40 {
+            0x40 + byte % 0x40
+        } else {
+            byte
+        };
+        match opcode {
+            0x01 => Self::Return,
+            0x02 => Self::ReturnFactory,
+            0x03 => Self::PushZero,
+            0x04 => Self::Multiply,
+            0x05 => Self::Add,
+            0x06 => Self::Subtract,
+            0x07 => Self::Divide,
+            0x08 => Self::Modulo,
+            0x09 => Self::Negate,
+            0x0A => Self::JoinString,
+            0x0B => Self::JoinPaddedString,
+            0x0C => Self::LessThan,
+            0x0D => Self::LessThanOrEqual,
+            0x0E => Self::NotEqual,
+            0x0F => Self::Equal,
+            0x10 => Self::GreaterThan,
+            0x11 => Self::GreaterThanOrEqual,
+            0x12 => Self::And,
+            0x13 => Self::Or,
+            0x14 => Self::Not,
+            0x15 => Self::ContainsString,
+            0x16 => Self::StartsWithString,
+            0x17 => Self::GetChunk,
+            0x18 => Self::HiliteChunk,
+            0x19 => Self::OntoSprite,
+            0x1A => Self::IntoSprite,
+            0x1B => Self::GetField,
+            0x1C => Self::StartTell,
+            0x1D => Self::EndTell,
+            0x1E => Self::PushList,
+            0x1F => Self::PushPropertyList,
+            0x21 => Self::Swap,
+            0x41 => Self::PushInt8,
+            0x42 => Self::PushArgListNoReturn,
+            0x43 => Self::PushArgList,
+            0x44 => Self::PushConstant,
+            0x45 => Self::PushSymbol,
+            0x46 => Self::PushVarRef,
+            0x48 => Self::GetGlobal2,
+            0x49 => Self::GetGlobal,
+            0x4A => Self::GetProperty,
+            0x4B => Self::GetParameter,
+            0x4C => Self::GetLocal,
+            0x4E => Self::SetGlobal2,
+            0x4F => Self::SetGlobal,
+            0x50 => Self::SetProperty,
+            0x51 => Self::SetParameter,
+            0x52 => Self::SetLocal,
+            0x53 => Self::Jump,
+            0x54 => Self::EndRepeat,
+            0x55 => Self::JumpIfZero,
+            0x56 => Self::LocalCall,
+            0x57 => Self::ExternalCall,
+            0x58 => Self::ObjectCallOldStyle,
+            0x59 => Self::Put,
+            0x5A => Self::PutChunk,
+            0x5B => Self::DeleteChunk,
+            0x5C => Self::Get,
+            0x5D => Self::Set,
+            0x5F => Self::GetMovieProperty,
+            0x60 => Self::SetMovieProperty,
+            0x61 => Self::GetObjectProperty,
+            0x62 => Self::SetObjectProperty,
+            0x63 => Self::TellCall,
+            0x64 => Self::Peek,
+            0x65 => Self::Pop,
+            0x66 => Self::TheBuiltin,
+            0x67 => Self::ObjectCall,
+            0x6D => Self::PushChunkVarRef,
+            0x6E => Self::PushInt16,
+            0x6F => Self::PushInt32,
+            0x70 => Self::GetChainedProperty,
+            0x71 => Self::PushFloat32,
+            0x72 => Self::GetTopLevelProperty,
+            0x73 => Self::NewObject,
+            other => Self::Unknown(other),
+        }
+    }
+
+    pub const fn has_operand(self) -> bool {
+        match self {
+            Self::Unknown(opcode) => opcode >= 0x40,
+            _ => !matches!(
+                self,
+                Self::Return
+                    | Self::ReturnFactory
+                    | Self::PushZero
+                    | Self::Multiply
+                    | Self::Add
+                    | Self::Subtract
+                    | Self::Divide
+                    | Self::Modulo
+                    | Self::Negate
+                    | Self::JoinString
+                    | Self::JoinPaddedString
+                    | Self::LessThan
+                    | Self::LessThanOrEqual
+                    | Self::NotEqual
+                    | Self::Equal
+                    | Self::GreaterThan
+                    | Self::GreaterThanOrEqual
+                    | Self::And
+                    | Self::Or
+                    | Self::Not
+                    | Self::ContainsString
+                    | Self::StartsWithString
+                    | Self::GetChunk
+                    | Self::HiliteChunk
+                    | Self::OntoSprite
+                    | Self::IntoSprite
+                    | Self::GetField
+                    | Self::StartTell
+                    | Self::EndTell
+                    | Self::PushList
+                    | Self::PushPropertyList
+                    | Self::Swap
+            ),
+        }
+    }
+
+    /// The mnemonic used by the disassembly
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Return => "ret",
+            Self::ReturnFactory => "retfactory",
+            Self::PushZero => "pushzero",
+            Self::Multiply => "mul",
+            Self::Add => "add",
+            Self::Subtract => "sub",
+            Self::Divide => "div",
+            Self::Modulo => "mod",
+            Self::Negate => "inv",
+            Self::JoinString => "joinstr",
+            Self::JoinPaddedString => "joinpadstr",
+            Self::LessThan => "lt",
+            Self::LessThanOrEqual => "lteq",
+            Self::NotEqual => "nteq",
+            Self::Equal => "eq",
+            Self::GreaterThan => "gt",
+            Self::GreaterThanOrEqual => "gteq",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Not => "not",
+            Self::ContainsString => "containsstr",
+            Self::StartsWithString => "contains0str",
+            Self::GetChunk => "getchunk",
+            Self::HiliteChunk => "hilitechunk",
+            Self::OntoSprite => "ontospr",
+            Self::IntoSprite => "intospr",
+            Self::GetField => "getfield",
+            Self::StartTell => "starttell",
+            Self::EndTell => "endtell",
+            Self::PushList => "pushlist",
+            Self::PushPropertyList => "pushproplist",
+            Self::Swap => "swap",
+            Self::PushInt8 => "pushint8",
+            Self::PushArgListNoReturn => "pusharglistnoret",
+            Self::PushArgList => "pusharglist",
+            Self::PushConstant => "pushcons",
+            Self::PushSymbol => "pushsymb",
+            Self::PushVarRef => "pushvarref",
+            Self::GetGlobal2 => "getglobal2",
+            Self::GetGlobal => "getglobal",
+            Self::GetProperty => "getprop",
+            Self::GetParameter => "getparam",
+            Self::GetLocal => "getlocal",
+            Self::SetGlobal2 => "setglobal2",
+            Self::SetGlobal => "setglobal",
+            Self::SetProperty => "setprop",
+            Self::SetParameter => "setparam",
+            Self::SetLocal => "setlocal",
+            Self::Jump => "jmp",
+            Self::EndRepeat => "endrepeat",
+            Self::JumpIfZero => "jmpifz",
+            Self::LocalCall => "localcall",
+            Self::ExternalCall => "extcall",
+            Self::ObjectCallOldStyle => "oldobjcall",
+            Self::Put => "put",
+            Self::PutChunk => "putchunk",
+            Self::DeleteChunk => "deletechunk",
+            Self::Get => "get",
+            Self::Set => "set",
+            Self::GetMovieProperty => "getmovieprop",
+            Self::SetMovieProperty => "setmovieprop",
+            Self::GetObjectProperty => "getobjprop",
+            Self::SetObjectProperty => "setobjprop",
+            Self::TellCall => "tellcall",
+            Self::Peek => "peek",
+            Self::Pop => "pop",
+            Self::TheBuiltin => "thebuiltin",
+            Self::ObjectCall => "objcall",
+            Self::PushChunkVarRef => "pushchunkvarref",
+            Self::PushInt16 => "pushint16",
+            Self::PushInt32 => "pushint32",
+            Self::GetChainedProperty => "getchainedprop",
+            Self::PushFloat32 => "pushfloat32",
+            Self::GetTopLevelProperty => "gettoplevelprop",
+            Self::NewObject => "newobj",
+            Self::Unknown(_) => "unknown",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opcodes_ignore_the_operand_width() {
+        assert_eq!(LingoOpcode::from_byte(0x01), LingoOpcode::Return);
+        assert_eq!(LingoOpcode::from_byte(0x21), LingoOpcode::Swap);
+        for byte in [0x41, 0x81, 0xC1] {
+            assert_eq!(LingoOpcode::from_byte(byte), LingoOpcode::PushInt8);
+        }
+        assert_eq!(LingoOpcode::from_byte(0x93), LingoOpcode::Jump);
+        assert_eq!(LingoOpcode::from_byte(0x20), LingoOpcode::Unknown(0x20));
+        assert_eq!(LingoOpcode::from_byte(0x87), LingoOpcode::Unknown(0x47));
+        assert!(!LingoOpcode::Add.has_operand());
+        assert!(LingoOpcode::GetLocal.has_operand());
+    }
+
+    #[test]
+    fn reads_operands_of_every_width() {
+        let bytecode = [
+            0x05, // add
+            0x41, 0xFF, // push -1
+            0x4C, 0xFF, // get local 255
+            0x81, 0xFF, 0xFE, // push -2
+            0x8C, 0x01, 0x00, // get local 256
+            0xC1, 0x00, 0x01, 0x00, 0x00, // push 65536
+        ];
+        let instructions: Vec<(u32, LingoOpcode, i32)> = read_bytecode(&bytecode)
+            .unwrap()
+            .iter()
+            .map(|instruction| {
+                (
+                    instruction.position,
+                    instruction.opcode,
+                    instruction.operand,
+                )
+            })
+            .collect();
+        assert_eq!(
+            instructions,
+            [
+                (0, LingoOpcode::Add, 0),
+                (1, LingoOpcode::PushInt8, -1),
+                (3, LingoOpcode::GetLocal, 255),
+                (5, LingoOpcode::PushInt8, -2),
+                (8, LingoOpcode::GetLocal, 256),
+                (11, LingoOpcode::PushInt8, 65536),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_missing_operand() {
+        assert!(read_bytecode(&[0x8C, 0x01]).is_err());
+    }
+
+    #[test]
+    fn variable_numbering_follows_the_version() {
+        let index = |capital_x, version| {
+            LingoContext::new(Vec::new(), HashMap::new(), capital_x, version).variable_index(24)
+        };
+        assert_eq!(index(false, 404), 4);
+        assert_eq!(index(false, 600), 3);
+        assert_eq!(index(false, 0), 3);
+        assert_eq!(index(true, 404), 24);
+        assert_eq!(index(true, 850), 24);
+    }
+}
@@ -0,0 +1,400 @@
+use std::io::{self, Cursor};
+
+use lazy_static::lazy_static;
+
+use super::{
+    config::{MovieConfig, MoviePlatform},
+    ChunkReader, Endianness,
+};
+
+// Bitmaps of 8 bits or less store palette indexes, the palette is either one of the builtin system palettes
+// or a palette cast member (type 4) that holds a CLUT chunk
+
+// builtin palettes are stored as negative ids, offset by one
+const CLUT_SYSTEM_MAC: i16 = -1;
+const CLUT_SYSTEM_WIN: i16 = -101;
+
+// stored darkest first, the mac system palette runs from white to black
+const PALETTE_MAC: &[u8] = &[
+    0, 0, 0, 17, 17, 17, 34, 34, 34, 68, 68, 68, 85, 85, 85, 119, 119, 119, 136, 136, 136, 170,
+    170, 170, 187, 187, 187, 221, 221, 221, 238, 238, 238, 0, 0, 17, 0, 0, 34, 0, 0, 68, 0, 0, 85,
+    0, 0, 119, 0, 0, 136, 0, 0, 170, 0, 0, 187, 0, 0, 221, 0, 0, 238, 0, 17, 0, 0, 34, 0, 0, 68, 0,
+    0, 85, 0, 0, 119, 0, 0, 136, 0, 0, 170, 0, 0, 187, 0, 0, 221, 0, 0, 238, 0, 17, 0, 0, 34, 0, 0,
+    68, 0, 0, 85, 0, 0, 119, 0, 0, 136, 0, 0, 170, 0, 0, 187, 0, 0, 221, 0, 0, 238, 0, 0, 0, 0, 51,
+    0, 0, 102, 0, 0, 153, 0, 0, 204, 0, 0, 255, 0, 51, 0, 0, 51, 51, 0, 51, 102, 0, 51, 153, 0, 51,
+    204, 0, 51, 255, 0, 102, 0, 0, 102, 51, 0, 102, 102, 0, 102, 153, 0, 102, 204, 0, 102, 255, 0,
+    153, 0, 0, 153, 51, 0, 153, 102, 0, 153, 153, 0, 153, 204, 0, 153, 255, 0, 204, 0, 0, 204, 51,
+    0, 204, 102, 0, 204, 153, 0, 204, 204, 0, 204, 255, 0, 255, 0, 0, 255, 51, 0, 255, 102, 0, 255,
+    153, 0, 255, 204, 0, 255, 255, 51, 0, 0, 51, 0, 51, 51, 0, 102, 51, 0, 153, 51, 0, 204, 51, 0,
+    255, 51, 51, 0, 51, 51, 51, 51, 51, 102, 51, 51, 153, 51, 51, 204, 51, 51, 255, 51, 102, 0, 51,
+    102, 51, 51, 102, 102, 51, 102, 153, 51, 102, 204, 51, 102, 255, 51, 153, 0, 51, 153, 51, 51,
+    153, 102, 51, 153, 153, 51, 153, 204, 51, 153, 255, 51, 204, 0, 51, 204, 51, 51, 204, 102, 51,
+    204, 153, 51, 204, 204, 51, 204, 255, 51, 255, 0, 51, 255, 51, 51, 255, 102, 51, 255, 153, 51,
+    255, 204, 51, 255, 255, 102, 0, 0, 102, 0, 51, 102, 0, 102, 102, 0, 153, 102, 0, 204, 102, 0,
+    255, 102, 51, 0, 102, 51, 51, 102, 51, 102, 102, 51, 153, 102, 51, 204, 102, 51, 255, 102, 102,
+    0, 102, 102, 51, 102, 102, 102, 102, 102, 153, 102, 102, 204, 102, 102, 255, 102, 153, 0, 102,
+    153, 51, 102, 153, 102, 102, 153, 153, 102, 153, 204, 102, 153, 255, 102, 204, 0, 102, 204, 51,
+    102, 204, 102, 102, 204, 153, 102, 204, 204, 102, 204, 255, 102, 255, 0, 102, 255, 51, 102,
+    255, 102, 102, 255, 153, 102, 255, 204, 102, 255, 255, 153, 0, 0, 153, 0, 51, 153, 0, 102, 153,
+    0, 153, 153, 0, 204, 153, 0, 255, 153, 51, 0, 153, 51, 51, 153, 51, 102, 153, 51, 153, 153, 51,
+    204, 153, 51, 255, 153, 102, 0, 153, 102, 51, 153, 102, 102, 153, 102, 153, 153, 102, 204, 153,
+    102, 255, 153, 153, 0, 153, 153, 51, 153, 153, 102, 153, 153, 153, 153, 153, 204, 153, 153,
+    255, 153, 204, 0, 153, 204, 51, 153, 204, 102, 153, 204, 153, 153, 204, 204, 153, 204, 255,
+    153, 255, 0, 153, 255, 51, 153, 255, 102, 153, 255, 153, 153, 255, 204, 153, 255, 255, 204, 0,
+    0, 204, 0, 51, 204, 0, 102, 204, 0, 153, 204, 0, 204, 204, 0, 255, 204, 51, 0, 204, 51, 51,
+    204, 51, 102, 204, 51, 153, 204, 51, 204, 204, 51, 255, 204, 102, 0, 204, 102, 51, 204, 102,
+    102, 204, 102, 153, 204, 102, 204, 204, 102, 255, 204, 153, 0, 204, 153, 51, 204, 153, 102,
+    204, 153, 153, 204, 153, 204, 204, 153, 255, 204, 204, 0, 204, 204, 51, 204, 204, 102, 204,
+    204, 153, 204, 204, 204, 204, 204, 255, 204, 255, 0, 204, 255, 51, 204, 255, 102, 204, 255,
+    153, 204, 255, 204, 204, 255, 255, 255, 0, 0, 255, 0, 51, 255, 0, 102, 255, 0, 153, 255, 0,
+    204, 255, 0, 255, 255, 51, 0, 255, 51, 51, 255, 51, 102, 255, 51, 153, 255, 51, 204, 255, 51,
+    255, 255, 102, 0, 255, 102, 51, 255, 102, 102, 255, 102, 153, 255, 102, 204, 255, 102, 255,
+    255, 153, 0, 255, 153, 51, 255, 153, 102, 255, 153, 153, 255, 153, 204, 255, 153, 255, 255,
+    204, 0, 255, 204, 51, 255, 204, 102, 255, 204, 153, 255, 204, 204, 255, 204, 255, 255, 255, 0,
+    255, 255, 51, 255, 255, 102, 255, 255, 153, 255, 255, 204, 255, 255, 255,
+];
+
+// the twenty static windows colours, the windows system palette is the mac one with these at both ends
+const WIN_STATIC_LOW: [[u8; 3]; 10] = [
+    [0, 0, 0],
+    [128, 0, 0],
+    [0, 128, 0],
+    [128, 128, 0],
+    [0, 0, 128],
+    [128, 0, 128],
+    [0, 128, 128],
+    [192, 192, 192],
+    [192, 220, 192],
+    [166, 202, 240],
+];
+const WIN_STATIC_HIGH: [[u8; 3]; 10] = [
+    [255, 251, 240],
+    [160, 160, 164],
+    [128, 128, 128],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [0, 0, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+// bitmaps with fewer bits use the smaller system palettes
+const BLACK_WHITE: [[u8; 3]; 2] = [[255, 255, 255], [0, 0, 0]];
+const GRAYSCALE_4: [[u8; 3]; 4] = [[255, 255, 255], [170, 170, 170], [85, 85, 85], [0, 0, 0]];
+const MAC_16: [[u8; 3]; 16] = [
+    [255, 255, 255],
+    [252, 243, 5],
+    [255, 100, 2],
+    [221, 8, 6],
+    [242, 8, 132],
+    [70, 0, 165],
+    [0, 0, 212],
+    [2, 171, 234],
+    [31, 183, 20],
+    [0, 100, 17],
+    [86, 44, 5],
+    [144, 113, 58],
+    [192, 192, 192],
+    [128, 128, 128],
+    [64, 64, 64],
+    [0, 0, 0],
+];
+const WIN_16: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [128, 0, 0],
+    [0, 128, 0],
+    [128, 128, 0],
+    [0, 0, 128],
+    [128, 0, 128],
+    [0, 128, 128],
+    [192, 192, 192],
+    [128, 128, 128],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [0, 0, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+
+lazy_static! {
+    pub static ref SYSTEM_BLACK_WHITE: MacromediaPalette = MacromediaPalette {
+        colors: BLACK_WHITE.to_vec(),
+    };
+    pub static ref SYSTEM_GRAYSCALE_4: MacromediaPalette = MacromediaPalette {
+        colors: GRAYSCALE_4.to_vec(),
+    };
+    pub static ref SYSTEM_MAC_16: MacromediaPalette = MacromediaPalette {
+        colors: MAC_16.to_vec(),
+    };
+    pub static ref SYSTEM_WIN_16: MacromediaPalette = MacromediaPalette {
+        colors: WIN_16.to_vec(),
+    };
+    pub static ref SYSTEM_MAC: MacromediaPalette = MacromediaPalette {
+        colors: PALETTE_MAC
+            .chunks_exact(3)
+            .rev()
+            .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+            .collect(),
+    };
+    pub static ref SYSTEM_WIN: MacromediaPalette = {
+        let mut colors = SYSTEM_MAC.colors.clone();
+        colors[..10].copy_from_slice(&WIN_STATIC_LOW);
+        colors[246..].copy_from_slice(&WIN_STATIC_HIGH);
+        MacromediaPalette { colors }
+    };
+}
+
+/// A palette indexed by the bytes of a bitmap
+#[derive(Clone, Debug)]
+pub struct MacromediaPalette {
+    pub colors: Vec<[u8; 3]>,
+}
+
+impl MacromediaPalette {
+    /// Out of range indexes are black, like director draws them
+    pub fn color(&self, index: u8) -> [u8; 3] {
+        self.colors.get(index as usize).copied().unwrap_or_default()
+    }
+}
+
+/// Which palette a bitmap was drawn with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteRef {
+    SystemMac,
+    SystemWin,
+    /// Builtin palettes we do not have (rainbow, grayscale, pastels...), these fall back to the mac system palette
+    Builtin(i16),
+    Member {
+        cast_lib: i16,
+        member: i16,
+    },
+}
+
+impl PaletteRef {
+    /// The builtin palette for a bitmap of `bit_depth` bits, `None` when the palette is a cast member
+    pub fn system_palette(self, bit_depth: u8) -> Option<&'static MacromediaPalette> {
+        let palette: &MacromediaPalette = match (self, bit_depth) {
+            (Self::Member { .. }, _) => return None,
+            (_, 1) => &SYSTEM_BLACK_WHITE,
+            (_, 2) => &SYSTEM_GRAYSCALE_4,
+            (Self::SystemWin, 4) => &SYSTEM_WIN_16,
+            (_, 4) => &SYSTEM_MAC_16,
+            (Self::SystemWin, _) => &SYSTEM_WIN,
+            _ => &SYSTEM_MAC,
+        };
+        Some(palette)
+    }
+
+    /// The palette a bitmap of `bit_depth` bits is drawn with, `members` looks up the palette members of the movie
+    /// by cast library and number, a member that can not be found falls back to the default palette
+    pub fn resolve<'a>(
+        self,
+        bit_depth: u8,
+        config: Option<&MovieConfig>,
+        members: impl Fn(i16, u32) -> Option<&'a MacromediaPalette>,
+    ) -> &'a MacromediaPalette {
+        if let Some(system_palette) = self.system_palette(bit_depth) {
+            return system_palette;
+        }
+        match self {
+            Self::Member { cast_lib, member } => member_palette(&members, cast_lib, member)
+                .unwrap_or_else(|| default_palette(config, members)),
+            _ => default_palette(config, members),
+        }
+    }
+
+    /// The score and the movie config count the builtin palettes from -1 where bitmaps count them from 0
+    /// 0 means no palette was set
+    pub const fn from_score_id(cast_lib: i16, palette: i16) -> Option<Self> {
+        if palette == 0 {
+            return None;
+        }
+        if palette > 0 {
+            return Some(Self::from_clut_id(cast_lib, palette));
+        }
+        Some(Self::from_clut_id(cast_lib, palette + 1))
+    }
+
+    pub const fn from_clut_id(cast_lib: i16, clut_id: i16) -> Self {
+        if clut_id > 0 {
+            return Self::Member {
+                cast_lib,
+                member: clut_id,
+            };
+        }
+        match clut_id - 1 {
+            CLUT_SYSTEM_MAC => Self::SystemMac,
+            CLUT_SYSTEM_WIN => Self::SystemWin,
+            builtin => Self::Builtin(builtin),
+        }
+    }
+}
+
+/// The default palette of the movie, or else the system palette of the platform it was made on
+pub fn default_palette<'a>(
+    config: Option<&MovieConfig>,
+    members: impl Fn(i16, u32) -> Option<&'a MacromediaPalette>,
+) -> &'a MacromediaPalette {
+    let Some(config) = config else {
+        return &SYSTEM_MAC;
+    };
+    match config.default_palette {
+        Some(PaletteRef::Member { cast_lib, member }) => {
+            if let Some(palette) = member_palette(&members, cast_lib, member) {
+                return palette;
+            }
+        }
+        Some(PaletteRef::SystemWin) => return &SYSTEM_WIN,
+        Some(_) => return &SYSTEM_MAC,
+        None => {}
+    }
+    if config.platform == MoviePlatform::Windows {
+        &SYSTEM_WIN
+    } else {
+        &SYSTEM_MAC
+    }
+}
+
+fn member_palette<'a>(
+    members: impl Fn(i16, u32) -> Option<&'a MacromediaPalette>,
+    cast_lib: i16,
+    member: i16,
+) -> Option<&'a MacromediaPalette> {
+    members(cast_lib, u32::try_from(member).ok()?)
+}
+
+/// Reads a CLUT chunk, every colour is three 16 bit channels of which only the high byte matters
+pub fn read_clut(data: &[u8]) -> io::Result<MacromediaPalette> {
+    let mut clut = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+
+    let mut colors = Vec::with_capacity(data.len() / 6);
+    for _ in 0..data.len() / 6 {
+        let [r, _] = clut.u16()?.to_be_bytes();
+        let [g, _] = clut.u16()?.to_be_bytes();
+        let [b, _] = clut.u16()?.to_be_bytes();
+        colors.push([r, g, b]);
+    }
+    Ok(MacromediaPalette { colors })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [u8; 3] = [0xFF; 3];
+    const BLACK: [u8; 3] = [0; 3];
+
+    fn config(default_palette: Option<PaletteRef>, platform: MoviePlatform) -> MovieConfig {
+        MovieConfig {
+            version: 1218,
+            stage_top: 0,
+            stage_left: 0,
+            stage_bottom: 480,
+            stage_right: 640,
+            tempo: 0,
+            default_palette,
+            platform,
+        }
+    }
+
+    #[test]
+    fn builtin_palettes() {
+        assert_eq!(SYSTEM_MAC.colors.len(), 256);
+        assert_eq!(SYSTEM_MAC.color(0), WHITE);
+        assert_eq!(SYSTEM_MAC.color(255), BLACK);
+        assert_eq!(SYSTEM_WIN.colors.len(), 256);
+        assert_eq!(SYSTEM_WIN.color(0), BLACK);
+        assert_eq!(SYSTEM_WIN.color(255), WHITE);
+        // between the static colours it is the mac palette
+        assert_eq!(SYSTEM_WIN.color(100), SYSTEM_MAC.color(100));
+        assert_eq!(SYSTEM_WIN_16.color(15), WHITE);
+        assert_eq!(SYSTEM_BLACK_WHITE.colors.len(), 2);
+        assert_eq!(SYSTEM_GRAYSCALE_4.colors.len(), 4);
+    }
+
+    #[test]
+    fn system_palette_fits_the_bit_depth() {
+        let palette = |palette_ref: PaletteRef, bit_depth| {
+            palette_ref.system_palette(bit_depth).unwrap().colors.len()
+        };
+        assert_eq!(palette(PaletteRef::SystemWin, 1), 2);
+        assert_eq!(palette(PaletteRef::SystemWin, 2), 4);
+        assert_eq!(palette(PaletteRef::SystemWin, 4), 16);
+        assert_eq!(palette(PaletteRef::Builtin(-3), 8), 256);
+        let member = PaletteRef::Member {
+            cast_lib: 1,
+            member: 5,
+        };
+        assert!(member.system_palette(8).is_none());
+    }
+
+    #[test]
+    fn score_ids_are_off_by_one() {
+        assert_eq!(PaletteRef::from_score_id(1, 0), None);
+        assert_eq!(
+            PaletteRef::from_score_id(2, 7),
+            Some(PaletteRef::Member {
+                cast_lib: 2,
+                member: 7
+            })
+        );
+        assert_eq!(
+            PaletteRef::from_score_id(0, -1),
+            Some(PaletteRef::SystemMac)
+        );
+        assert_eq!(
+            PaletteRef::from_score_id(0, -101),
+            Some(PaletteRef::SystemWin)
+        );
+        assert_eq!(
+            PaletteRef::from_score_id(0, -3),
+            Some(PaletteRef::Builtin(-3))
+        );
+        assert_eq!(PaletteRef::from_clut_id(0, 0), PaletteRef::SystemMac);
+        assert_eq!(PaletteRef::from_clut_id(0, -100), PaletteRef::SystemWin);
+    }
+
+    #[test]
+    fn members_are_looked_up_in_their_cast() {
+        let red = MacromediaPalette {
+            colors: vec![[0xFF, 0, 0]],
+        };
+        let green = MacromediaPalette {
+            colors: vec![[0, 0xFF, 0]],
+        };
+        let members = |cast_lib, member| match (cast_lib, member) {
+            (1, 5) => Some(&red),
+            (2, 5) => Some(&green),
+            _ => None,
+        };
+        let member = |cast_lib| PaletteRef::Member {
+            cast_lib,
+            member: 5,
+        };
+
+        let windows = config(None, MoviePlatform::Windows);
+        assert_eq!(
+            member(2).resolve(8, Some(&windows), members).color(0),
+            [0, 0xFF, 0]
+        );
+        assert_eq!(
+            member(1).resolve(8, Some(&windows), members).color(0),
+            [0xFF, 0, 0]
+        );
+        // a member that is not there falls back to the movie default and then the platform
+        assert_eq!(
+            member(3).resolve(8, Some(&windows), members).color(0),
+            BLACK
+        );
+        let with_default = config(Some(member(2)), MoviePlatform::Mac);
+        assert_eq!(
+            member(3).resolve(8, Some(&with_default), members).color(0),
+            [0, 0xFF, 0]
+        );
+        assert_eq!(member(3).resolve(8, None, members).color(0), WHITE);
+    }
+}
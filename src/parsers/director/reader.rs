@@ -0,0 +1,237 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use yore::code_pages::CP1252;
+
+use super::Endianness;
+
+/// Reads the fields of a chunk in the byte order it was written in.
+/// Every read is checked against the end of the chunk, so a wrong length can not read into the next chunk
+pub struct ChunkReader<R> {
+    inner: R,
+    endian: Endianness,
+    start: u64,
+    end: u64,
+}
+
+#[allow(dead_code)] // not every chunk needs every kind of read yet
+impl<R: Read + Seek> ChunkReader<R> {
+    /// A reader spanning the whole of `inner`
+    pub fn new(mut inner: R, endian: Endianness) -> io::Result<Self> {
+        let end = inner.seek(SeekFrom::End(0))?;
+        inner.seek(SeekFrom::Start(0))?;
+        Ok(Self {
+            inner,
+            endian,
+            start: 0,
+            end,
+        })
+    }
+
+    pub const fn endian(&self) -> Endianness {
+        self.endian
+    }
+
+    pub const fn set_endian(&mut self, endian: Endianness) {
+        self.endian = endian;
+    }
+
+    pub const fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
+
+    pub fn position(&mut self) -> io::Result<u64> {
+        Ok(self.inner.stream_position()? - self.start)
+    }
+
+    pub fn remaining(&mut self) -> io::Result<u64> {
+        Ok(self.len().saturating_sub(self.position()?))
+    }
+
+    /// Seeks relative to the start of the chunk
+    pub fn seek(&mut self, offset: u64) -> io::Result<()> {
+        if offset > self.len() {
+            return Err(out_of_bounds(offset, self.len()));
+        }
+        self.inner.seek(SeekFrom::Start(self.start + offset))?;
+        Ok(())
+    }
+
+    pub fn skip(&mut self, amount: i64) -> io::Result<()> {
+        let target = self
+            .position()?
+            .checked_add_signed(amount)
+            .ok_or_else(|| out_of_bounds(0, self.len()))?;
+        self.seek(target)
+    }
+
+    /// A reader over `length` bytes at `offset`, it can not read outside of those bytes
+    pub fn sub_reader(&mut self, offset: u64, length: u64) -> io::Result<ChunkReader<&mut R>> {
+        if offset + length > self.len() {
+            return Err(out_of_bounds(offset + length, self.len()));
+        }
+        let start = self.start + offset;
+        self.inner.seek(SeekFrom::Start(start))?;
+        Ok(ChunkReader {
+            inner: &mut self.inner,
+            endian: self.endian,
+            start,
+            end: start + length,
+        })
+    }
+
+    fn ensure(&mut self, size: u64) -> io::Result<()> {
+        let position = self.position()?;
+        if position + size > self.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "read of {size} bytes at {position} runs past the end of the chunk ({})",
+                    self.len()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn u8(&mut self) -> io::Result<u8> {
+        self.ensure(1)?;
+        self.inner.read_u8()
+    }
+
+    pub fn i8(&mut self) -> io::Result<i8> {
+        self.ensure(1)?;
+        self.inner.read_i8()
+    }
+
+    pub fn u16(&mut self) -> io::Result<u16> {
+        self.ensure(2)?;
+        match self.endian {
+            Endianness::Big => self.inner.read_u16::<BigEndian>(),
+            Endianness::Little => self.inner.read_u16::<LittleEndian>(),
+        }
+    }
+
+    pub fn i16(&mut self) -> io::Result<i16> {
+        self.ensure(2)?;
+        match self.endian {
+            Endianness::Big => self.inner.read_i16::<BigEndian>(),
+            Endianness::Little => self.inner.read_i16::<LittleEndian>(),
+        }
+    }
+
+    pub fn u32(&mut self) -> io::Result<u32> {
+        self.ensure(4)?;
+        match self.endian {
+            Endianness::Big => self.inner.read_u32::<BigEndian>(),
+            Endianness::Little => self.inner.read_u32::<LittleEndian>(),
+        }
+    }
+
+    pub fn i32(&mut self) -> io::Result<i32> {
+        self.ensure(4)?;
+        match self.endian {
+            Endianness::Big => self.inner.read_i32::<BigEndian>(),
+            Endianness::Little => self.inner.read_i32::<LittleEndian>(),
+        }
+    }
+
+    /// A fourcc is stored as a u32, so in little endian files it reads backwards
+    pub fn fourcc(&mut self) -> io::Result<[u8; 4]> {
+        Ok(self.u32()?.to_be_bytes())
+    }
+
+    pub fn bytes(&mut self, length: usize) -> io::Result<Vec<u8>> {
+        self.ensure(length as u64)?;
+        let mut buffer = vec![0u8; length];
+        self.inner.read_exact(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// A variable length number, 7 bits per byte with the high bit set on all but the last byte.
+    /// Afterburner files use these, they read the same in both byte orders
+    pub fn varint(&mut self) -> io::Result<u32> {
+        let mut value = 0u32;
+        loop {
+            let byte = self.u8()?;
+            value = (value << 7) | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// A string prefixed with a single length byte
+    pub fn pascal_string(&mut self) -> io::Result<String> {
+        let length = self.u8()?;
+        Ok(CP1252.decode(&self.bytes(length as usize)?).to_string())
+    }
+}
+
+fn out_of_bounds(offset: u64, length: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("offset {offset} is outside of the chunk ({length})"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn reads_in_both_byte_orders() {
+        let data = [0x12, 0x34, 0x56, 0x78];
+        let mut big = ChunkReader::new(Cursor::new(data), Endianness::Big).unwrap();
+        assert_eq!(big.u32().unwrap(), 0x1234_5678);
+        let mut little = ChunkReader::new(Cursor::new(data), Endianness::Little).unwrap();
+        assert_eq!(little.u16().unwrap(), 0x3412);
+        // fourccs of little endian files are stored backwards
+        let mut xfir = ChunkReader::new(Cursor::new(*b"XFIR"), Endianness::Little).unwrap();
+        assert_eq!(&xfir.fourcc().unwrap(), b"RIFX");
+    }
+
+    #[test]
+    fn stops_at_the_end_of_the_chunk() {
+        let mut reader = ChunkReader::new(Cursor::new([1, 2, 3]), Endianness::Big).unwrap();
+        assert_eq!(reader.u16().unwrap(), 0x0102);
+        assert_eq!(
+            reader.u16().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        // the failed read did not move on
+        assert_eq!(reader.u8().unwrap(), 3);
+        assert!(reader.u8().is_err());
+        assert!(reader.seek(4).is_err());
+        assert!(reader.skip(-4).is_err());
+        assert!(reader.bytes(1).is_err());
+    }
+
+    #[test]
+    fn sub_reader_can_not_leave_its_bytes() {
+        let mut reader =
+            ChunkReader::new(Cursor::new([0, 1, 2, 3, 4, 5]), Endianness::Big).unwrap();
+        assert!(reader.sub_reader(4, 3).is_err());
+        let mut sub_reader = reader.sub_reader(2, 2).unwrap();
+        assert_eq!(sub_reader.len(), 2);
+        assert_eq!(sub_reader.u8().unwrap(), 2);
+        assert_eq!(sub_reader.remaining().unwrap(), 1);
+        assert!(sub_reader.u16().is_err());
+        sub_reader.seek(0).unwrap();
+        assert_eq!(sub_reader.u16().unwrap(), 0x0203);
+    }
+
+    #[test]
+    fn reads_varints_and_pascal_strings() {
+        let mut reader =
+            ChunkReader::new(Cursor::new([0x81, 0x00, 0x02, b'h', b'i']), Endianness::Big).unwrap();
+        assert_eq!(reader.varint().unwrap(), 0x80);
+        assert_eq!(reader.pascal_string().unwrap(), "hi");
+    }
+}
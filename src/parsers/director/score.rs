@@ -0,0 +1,484 @@
+use std::io::{self, Cursor};
+
+use yore::code_pages::CP1252;
+
+use super::{ink::Ink, palette::PaletteRef, ChunkReader, Endianness};
+
+// Filmloops (SCVW) and the score (VWSC) share the same frame data
+// Every frame only stores what changed since the previous frame as (length, offset, bytes) runs
+// over one big buffer of channels, the first two channel slots hold the main channels (tempo, palette, sounds...)
+// and every slot after that is a sprite channel
+// The score wraps the frame data in a list of entries, the frames are the first entry
+
+const D4_CHANNEL_SIZE: usize = 20;
+const MAIN_CHANNEL_SLOTS: usize = 2;
+
+/// One sprite channel of one frame
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MacromediaSprite {
+    /// Sprite channel number, starting at 1 like in lingo
+    pub channel: u16,
+    pub sprite_type: u8,
+    pub cast_lib: i16,
+    pub member: u16,
+    pub script_cast_lib: i16,
+    pub script: u16,
+    pub ink: Ink,
+    pub trails: bool,
+    /// Only used by the blend ink, 0 is fully visible
+    pub blend: u8,
+    pub foreground: u8,
+    pub background: u8,
+    /// The position of the registration point of the member on the stage
+    pub pos_x: i16,
+    pub pos_y: i16,
+    pub width: i16,
+    pub height: i16,
+}
+
+impl MacromediaSprite {
+    pub const fn member_ref(&self) -> MacromediaMemberRef {
+        MacromediaMemberRef {
+            cast_lib: self.cast_lib,
+            member: self.member,
+        }
+    }
+
+    /// The member as lingo numbers it, members of other casts than the first are cast * 65536 + member
+    pub fn member_number(&self) -> u32 {
+        match u32::try_from(self.cast_lib) {
+            Ok(cast_lib) if cast_lib > 1 => cast_lib << 16 | u32::from(self.member),
+            _ => u32::from(self.member),
+        }
+    }
+}
+
+/// A cast member used by one of the main channels
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MacromediaMemberRef {
+    pub cast_lib: i16,
+    pub member: u16,
+}
+
+#[derive(Clone, Debug, Default)]
+#[allow(dead_code)]
+pub struct MacromediaFrame {
+    /// Frames per second from the tempo channel, 0 keeps the previous tempo
+    pub tempo: u8,
+    pub script: Option<MacromediaMemberRef>,
+    pub sounds: [Option<MacromediaMemberRef>; 2],
+    /// Director 4 transitions are builtin and not a member, those are left out
+    pub transition: Option<MacromediaMemberRef>,
+    pub palette: Option<PaletteRef>,
+    pub sprites: Vec<MacromediaSprite>,
+}
+
+/// The frames of a movie and the labels that name some of them
+#[derive(Clone, Debug, Default)]
+pub struct MacromediaScore {
+    pub frames: Vec<MacromediaFrame>,
+    /// Frame numbers start at 1, like in lingo
+    pub labels: Vec<(u16, String)>,
+}
+
+#[allow(dead_code)] // nothing builds rooms from the score yet
+impl MacromediaScore {
+    /// Frame `number`, counting from 1
+    pub fn frame(&self, number: u16) -> Option<&MacromediaFrame> {
+        self.frames.get(usize::from(number).checked_sub(1)?)
+    }
+
+    pub fn label_frame(&self, label: &str) -> Option<u16> {
+        self.labels
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(label))
+            .map(|(frame, _)| *frame)
+    }
+
+    pub fn sprites_on_frame(&self, number: u16) -> &[MacromediaSprite] {
+        self.frame(number)
+            .map_or(&[], |frame| frame.sprites.as_slice())
+    }
+
+    pub fn sprites_on_label(&self, label: &str) -> &[MacromediaSprite] {
+        self.label_frame(label)
+            .map_or(&[], |frame| self.sprites_on_frame(frame))
+    }
+}
+
+/// Decodes a VWSC chunk and, when the movie has one, its VWLB labels
+pub fn read_score(vwsc: &[u8], vwlb: Option<&[u8]>) -> io::Result<MacromediaScore> {
+    let mut score = ChunkReader::new(Cursor::new(vwsc), Endianness::Big)?;
+
+    let _total_length = score.u32()?;
+    let _header_type = score.i32()?; // -3 for director 5 and up
+    let offsets_offset = score.u32()?;
+    let entry_count = score.u32()?;
+    let _entry_count_plus_one = score.u32()?;
+    let _entry_size_sum = score.u32()?;
+
+    score.seek(u64::from(offsets_offset))?;
+    let mut offsets = Vec::new();
+    for _ in 0..=entry_count {
+        offsets.push(score.u32()?);
+    }
+    // the entries follow the offsets, the frame data is the first one
+    let entries_start = score.position()?;
+    let (Some(&frames_start), Some(&frames_end)) = (offsets.first(), offsets.get(1)) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "score without frame data",
+        ));
+    };
+    let frames_length = frames_end.saturating_sub(frames_start);
+    let frame_data = score
+        .sub_reader(
+            entries_start + u64::from(frames_start),
+            u64::from(frames_length),
+        )?
+        .bytes(frames_length as usize)?;
+
+    Ok(MacromediaScore {
+        frames: read_frames(&frame_data)?,
+        labels: vwlb.map(read_labels).transpose()?.unwrap_or_default(),
+    })
+}
+
+/// A VWLB chunk is a list of (frame, text offset) pairs followed by all the label texts glued together
+fn read_labels(vwlb: &[u8]) -> io::Result<Vec<(u16, String)>> {
+    let mut labels_reader = ChunkReader::new(Cursor::new(vwlb), Endianness::Big)?;
+    let count = usize::from(labels_reader.u16()?);
+
+    // one extra entry marks the end of the last text
+    let mut entries = Vec::with_capacity(count + 1);
+    for _ in 0..=count {
+        let frame = labels_reader.u16()?;
+        let text_offset = usize::from(labels_reader.u16()?);
+        entries.push((frame, text_offset));
+    }
+    let texts = &vwlb[(2 + (count + 1) * 4).min(vwlb.len())..];
+
+    Ok(entries
+        .windows(2)
+        .map(|pair| {
+            let (frame, start) = pair[0];
+            let end = pair[1].1.clamp(start, texts.len());
+            let text = CP1252.decode(texts.get(start..end).unwrap_or_default());
+            // anything after a return is a comment of the author
+            let label = text.split('\r').next().unwrap_or_default().to_owned();
+            (frame, label)
+        })
+        .collect())
+}
+
+/// Decodes the frame data of a filmloop or the first entry of a score
+pub fn read_frames(data: &[u8]) -> io::Result<Vec<MacromediaFrame>> {
+    let mut frames_reader = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+
+    let size = u64::from(frames_reader.u32()?).min(frames_reader.len());
+    let frames_offset = frames_reader.u32()?;
+    let _frame_count = frames_reader.u32()?;
+    let _frames_version = frames_reader.u16()?;
+    let channel_size = usize::from(frames_reader.u16()?); // 20 before director 5, 24 after
+    if channel_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame data with a channel size of 0",
+        ));
+    }
+    frames_reader.seek(u64::from(frames_offset))?;
+
+    let mut channels = Vec::<u8>::new();
+    let mut frames = Vec::new();
+
+    while frames_reader.position()? < size {
+        let frame_size = usize::from(frames_reader.u16()?);
+        let mut remaining = frame_size.saturating_sub(2);
+
+        while remaining > 0 {
+            let length = usize::from(frames_reader.u16()?);
+            let offset = usize::from(frames_reader.u16()?);
+            let changed = frames_reader.bytes(length)?;
+            remaining = remaining.saturating_sub(4 + length);
+
+            if channels.len() < offset + length {
+                channels.resize(offset + length, 0);
+            }
+            channels[offset..offset + length].copy_from_slice(&changed);
+        }
+        frames.push(read_frame(&channels, channel_size));
+    }
+
+    Ok(frames)
+}
+
+fn read_frame(channels: &[u8], channel_size: usize) -> MacromediaFrame {
+    let mut main_channels = channels
+        .get(..channel_size * MAIN_CHANNEL_SLOTS)
+        .unwrap_or(channels)
+        .to_vec();
+    main_channels.resize(channel_size * MAIN_CHANNEL_SLOTS, 0);
+
+    let sprites = channels
+        .chunks_exact(channel_size)
+        .enumerate()
+        .skip(MAIN_CHANNEL_SLOTS)
+        .filter_map(|(slot, channel)| {
+            read_sprite((slot - MAIN_CHANNEL_SLOTS + 1) as u16, channel).ok()
+        })
+        .filter(|sprite| sprite.member != 0)
+        .collect();
+
+    let main_channels = if channel_size == D4_CHANNEL_SIZE {
+        read_main_channels_d4(&main_channels)
+    } else {
+        read_main_channels_d5(&main_channels)
+    };
+    MacromediaFrame {
+        sprites,
+        // the slots are padded above, so these reads can not run out
+        ..main_channels.unwrap_or_default()
+    }
+}
+
+fn read_main_channels_d4(main_channels: &[u8]) -> io::Result<MacromediaFrame> {
+    let mut main = ChunkReader::new(Cursor::new(main_channels), Endianness::Big)?;
+    let script = u16::from(main.u8()?);
+    main.skip(3)?; // sound type, transition duration and chunk size
+    let tempo = main.u8()?;
+    let _transition_type = main.u8()?;
+    let sound1 = main.u16()?;
+    let sound2 = main.u16()?;
+    main.seek(D4_CHANNEL_SIZE as u64)?; // the palette has the second slot
+    let palette = main.i16()?;
+    Ok(MacromediaFrame {
+        tempo,
+        script: member_ref(0, script),
+        sounds: [member_ref(0, sound1), member_ref(0, sound2)],
+        transition: None,
+        palette: PaletteRef::from_score_id(0, palette),
+        sprites: Vec::new(),
+    })
+}
+
+fn read_main_channels_d5(main_channels: &[u8]) -> io::Result<MacromediaFrame> {
+    let mut main = ChunkReader::new(Cursor::new(main_channels), Endianness::Big)?;
+    let mut read_member_ref = || -> io::Result<Option<MacromediaMemberRef>> {
+        let cast_lib = main.i16()?;
+        Ok(member_ref(cast_lib, main.u16()?))
+    };
+    let script = read_member_ref()?;
+    let sound1 = read_member_ref()?;
+    let sound2 = read_member_ref()?;
+    let transition = read_member_ref()?;
+    main.skip(5)?; // the colours of the main channels in the score window
+    let tempo = main.u8()?;
+    main.skip(2)?;
+    let palette_cast_lib = main.i16()?;
+    let palette = main.i16()?;
+    Ok(MacromediaFrame {
+        tempo,
+        script,
+        sounds: [sound1, sound2],
+        transition,
+        palette: PaletteRef::from_score_id(palette_cast_lib, palette),
+        sprites: Vec::new(),
+    })
+}
+
+const fn member_ref(cast_lib: i16, member: u16) -> Option<MacromediaMemberRef> {
+    if member == 0 {
+        return None;
+    }
+    Some(MacromediaMemberRef { cast_lib, member })
+}
+
+fn read_sprite(channel_number: u16, channel: &[u8]) -> io::Result<MacromediaSprite> {
+    let mut sprite = ChunkReader::new(Cursor::new(channel), Endianness::Big)?;
+
+    if channel.len() == D4_CHANNEL_SIZE {
+        let script = u16::from(sprite.u8()?);
+        let sprite_type = sprite.u8()?;
+        let foreground = sprite.u8()?;
+        let background = sprite.u8()?;
+        let _thickness = sprite.u8()?;
+        let ink_data = sprite.u8()?;
+        let member = sprite.u16()?;
+        let pos_y = sprite.i16()?;
+        let pos_x = sprite.i16()?;
+        let height = sprite.i16()?;
+        let width = sprite.i16()?;
+        let _script = sprite.u16()?; // the byte above is used instead
+        let _color_code = sprite.u8()?;
+        let blend = sprite.u8()?;
+        return Ok(MacromediaSprite {
+            channel: channel_number,
+            sprite_type,
+            cast_lib: 0,
+            member,
+            script_cast_lib: 0,
+            script,
+            ink: Ink::from_ink_data(ink_data),
+            trails: ink_data & 0x40 != 0,
+            blend,
+            foreground,
+            background,
+            pos_x,
+            pos_y,
+            width,
+            height,
+        });
+    }
+
+    let sprite_type = sprite.u8()?;
+    let ink_data = sprite.u8()?;
+    let cast_lib = sprite.i16()?;
+    let member = sprite.u16()?;
+    let script_cast_lib = sprite.i16()?;
+    let script = sprite.u16()?;
+    let foreground = sprite.u8()?;
+    let background = sprite.u8()?;
+    let pos_y = sprite.i16()?;
+    let pos_x = sprite.i16()?;
+    let height = sprite.i16()?;
+    let width = sprite.i16()?;
+    let _color_code = sprite.u8()?;
+    let blend = sprite.u8()?;
+    Ok(MacromediaSprite {
+        channel: channel_number,
+        sprite_type,
+        cast_lib,
+        member,
+        script_cast_lib,
+        script,
+        ink: Ink::from_ink_data(ink_data),
+        trails: ink_data & 0x40 != 0,
+        blend,
+        foreground,
+        background,
+        pos_x,
+        pos_y,
+        width,
+        height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANNEL_SIZE: usize = 24;
+    const SPRITE_OFFSET: usize = CHANNEL_SIZE * MAIN_CHANNEL_SLOTS;
+
+    // two frames of director 5 frame data: a sprite in channel 1, then the same sprite moved to the right
+    fn frame_data() -> Vec<u8> {
+        let mut sprite = vec![
+            1, // sprite type
+            8, // matte ink
+            0, 1, // cast lib
+            0, 5, // member
+            0, 0, 0, 0, // script
+            255, 0, // colours
+            0, 10, // y
+            0, 20, // x
+            0, 30, // height
+            0, 40, // width
+            0, 100, // colour code and blend
+        ];
+        sprite.resize(CHANNEL_SIZE, 0);
+
+        let mut frames = Vec::new();
+        frames.extend_from_slice(&(2 + 4 + CHANNEL_SIZE as u16).to_be_bytes());
+        frames.extend_from_slice(&(CHANNEL_SIZE as u16).to_be_bytes());
+        frames.extend_from_slice(&(SPRITE_OFFSET as u16).to_be_bytes());
+        frames.extend_from_slice(&sprite);
+        // only x changes in the second frame
+        frames.extend_from_slice(&8u16.to_be_bytes());
+        frames.extend_from_slice(&2u16.to_be_bytes());
+        frames.extend_from_slice(&(SPRITE_OFFSET as u16 + 14).to_be_bytes());
+        frames.extend_from_slice(&25i16.to_be_bytes());
+
+        let header_length = 16;
+        let mut data = Vec::new();
+        data.extend_from_slice(&(header_length + frames.len() as u32).to_be_bytes());
+        data.extend_from_slice(&header_length.to_be_bytes());
+        data.extend_from_slice(&2u32.to_be_bytes());
+        data.extend_from_slice(&13u16.to_be_bytes());
+        data.extend_from_slice(&(CHANNEL_SIZE as u16).to_be_bytes());
+        data.extend_from_slice(&frames);
+        data
+    }
+
+    fn labels() -> Vec<u8> {
+        let mut vwlb = Vec::new();
+        vwlb.extend_from_slice(&2u16.to_be_bytes());
+        for (frame, offset) in [(1u16, 0u16), (2, 5), (0, 15)] {
+            vwlb.extend_from_slice(&frame.to_be_bytes());
+            vwlb.extend_from_slice(&offset.to_be_bytes());
+        }
+        vwlb.extend_from_slice(b"startloop\rnote");
+        vwlb
+    }
+
+    #[test]
+    fn reads_frames_as_changes() {
+        let frames = read_frames(&frame_data()).unwrap();
+        assert_eq!(frames.len(), 2);
+
+        let first = &frames[0].sprites;
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].channel, 1);
+        assert_eq!(
+            first[0].member_ref(),
+            MacromediaMemberRef {
+                cast_lib: 1,
+                member: 5
+            }
+        );
+        assert_eq!(first[0].ink, Ink::Matte);
+        assert_eq!((first[0].pos_x, first[0].pos_y), (20, 10));
+        assert_eq!((first[0].width, first[0].height), (40, 30));
+        assert_eq!(first[0].blend, 100);
+
+        // the rest of the channel carries over from the first frame
+        let second = &frames[1].sprites;
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].pos_x, 25);
+        assert_eq!(second[0].pos_y, 10);
+        assert_eq!(second[0].member, 5);
+    }
+
+    #[test]
+    fn rejects_frame_data_without_channels() {
+        let mut data = frame_data();
+        data[14..16].copy_from_slice(&0u16.to_be_bytes());
+        assert!(read_frames(&data).is_err());
+    }
+
+    #[test]
+    fn reads_score_with_labels() {
+        let frames = frame_data();
+        let mut vwsc = Vec::new();
+        let offsets_offset = 24u32;
+        vwsc.extend_from_slice(&0u32.to_be_bytes());
+        vwsc.extend_from_slice(&(-3i32).to_be_bytes());
+        vwsc.extend_from_slice(&offsets_offset.to_be_bytes());
+        vwsc.extend_from_slice(&1u32.to_be_bytes());
+        vwsc.extend_from_slice(&2u32.to_be_bytes());
+        vwsc.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+        vwsc.extend_from_slice(&0u32.to_be_bytes());
+        vwsc.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+        vwsc.extend_from_slice(&frames);
+
+        let score = read_score(&vwsc, Some(&labels())).unwrap();
+        assert_eq!(score.frames.len(), 2);
+        assert_eq!(
+            score.labels,
+            [(1, "start".to_owned()), (2, "loop".to_owned())]
+        );
+        assert_eq!(score.label_frame("LOOP"), Some(2));
+        assert_eq!(score.label_frame("end"), None);
+        assert_eq!(score.sprites_on_label("loop")[0].pos_x, 25);
+        assert!(score.sprites_on_frame(3).is_empty());
+    }
+}
@@ -0,0 +1,406 @@
+use std::io::{self, Cursor};
+
+use super::{palette::MacromediaPalette, ChunkReader, Endianness};
+
+// Shapes (type 8) are QuickDraw rectangles, ovals and lines described by their specific data, they have no pixels
+// Buttons (type 7) are text members with the kind of button tacked on, the label is in their STXT
+// Both are drawn here to RGBA so they can be used like bitmaps, with the registration point in the top left corner
+
+// the specific data of a text member up to the button type
+const BUTTON_TYPE_OFFSET: u64 = 28;
+const TEXT_RECT_OFFSET: u64 = 14;
+
+// line_direction for lines that go up from left to right
+const LINE_UP: u8 = 6;
+// quickdraw draws round rects with this corner diameter
+const ROUND_RECT_CORNER: f32 = 12.;
+// the box of a checkbox or radio button
+const CHECK_BOX_SIZE: i16 = 12;
+
+const BUTTON_FILL: [u8; 3] = [255, 255, 255];
+const BUTTON_LINE: [u8; 3] = [0, 0, 0];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShapeType {
+    Rectangle,
+    RoundRectangle,
+    Oval,
+    Line,
+    Unknown(u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonType {
+    PushButton,
+    CheckBox,
+    RadioButton,
+}
+
+#[derive(Clone, Debug)]
+pub struct MacromediaShape {
+    pub shape_type: ShapeType,
+    pub width: i16,
+    pub height: i16,
+    /// Index into the palette of the movie, the shape and its outline are drawn in this colour
+    pub foreground: u8,
+    pub filled: bool,
+    pub line_thickness: u8,
+    /// Lines go from the top left to the bottom right corner, unless this says bottom left to top right
+    pub line_direction: u8,
+}
+
+impl MacromediaShape {
+    /// RGBA pixels of the shape, everything outside of it is transparent
+    pub fn rasterize(&self, palette: &MacromediaPalette) -> Vec<u8> {
+        let color = palette.color(self.foreground);
+        let mut canvas = Canvas::new(self.width, self.height);
+        let area = (0, 0, self.width, self.height);
+        let fill = self.filled.then_some(color);
+        if self.shape_type == ShapeType::Line {
+            canvas.line(self.line_direction == LINE_UP, self.line_thickness, color);
+        } else {
+            canvas.shape(self.shape_type, area, fill, self.line_thickness, color);
+        }
+        canvas.rgba
+    }
+}
+
+/// The kind of a button member and the size of its box
+#[derive(Clone, Debug)]
+pub struct MacromediaButton {
+    pub button_type: ButtonType,
+    pub width: i16,
+    pub height: i16,
+}
+
+impl MacromediaButton {
+    /// RGBA pixels of the frame of the button, the label goes on top of it
+    pub fn rasterize(&self) -> Vec<u8> {
+        let mut canvas = Canvas::new(self.width, self.height);
+        let box_size = CHECK_BOX_SIZE.min(self.height);
+        let box_area = (0, (self.height - box_size) / 2, box_size, box_size);
+        match self.button_type {
+            ButtonType::PushButton => canvas.shape(
+                ShapeType::RoundRectangle,
+                (0, 0, self.width, self.height),
+                Some(BUTTON_FILL),
+                1,
+                BUTTON_LINE,
+            ),
+            ButtonType::CheckBox => canvas.shape(
+                ShapeType::Rectangle,
+                box_area,
+                Some(BUTTON_FILL),
+                1,
+                BUTTON_LINE,
+            ),
+            ButtonType::RadioButton => {
+                canvas.shape(ShapeType::Oval, box_area, Some(BUTTON_FILL), 1, BUTTON_LINE)
+            }
+        }
+        canvas.rgba
+    }
+}
+
+pub fn read_shape(specific_data: &[u8]) -> io::Result<MacromediaShape> {
+    let mut shape = ChunkReader::new(Cursor::new(specific_data), Endianness::Big)?;
+    shape.skip(2)?; // unknown
+    let shape_type = match shape.u8()? {
+        1 => ShapeType::Rectangle,
+        2 => ShapeType::RoundRectangle,
+        3 => ShapeType::Oval,
+        4 => ShapeType::Line,
+        other => ShapeType::Unknown(other),
+    };
+    let (width, height) = read_rect_size(&mut shape)?;
+    // the fill pattern is left out, which draws every shape solid and leaves the background colour unused
+    shape.skip(2)?;
+    let foreground = shape.u8()?;
+    shape.skip(1)?; // background colour
+    Ok(MacromediaShape {
+        shape_type,
+        width,
+        height,
+        foreground,
+        filled: shape.u8()? != 0,
+        line_thickness: shape.u8()?,
+        line_direction: shape.u8()?,
+    })
+}
+
+pub fn read_button(specific_data: &[u8]) -> io::Result<MacromediaButton> {
+    let mut button = ChunkReader::new(Cursor::new(specific_data), Endianness::Big)?;
+    button.seek(TEXT_RECT_OFFSET)?;
+    let (width, height) = read_rect_size(&mut button)?;
+    button.seek(BUTTON_TYPE_OFFSET)?;
+    let button_type = match button.u16()? {
+        2 => ButtonType::CheckBox,
+        3 => ButtonType::RadioButton,
+        _ => ButtonType::PushButton,
+    };
+    Ok(MacromediaButton {
+        button_type,
+        width,
+        height,
+    })
+}
+
+fn read_rect_size(reader: &mut ChunkReader<Cursor<&[u8]>>) -> io::Result<(i16, i16)> {
+    let top = reader.i16()?;
+    let left = reader.i16()?;
+    let bottom = reader.i16()?;
+    let right = reader.i16()?;
+    Ok((
+        right.saturating_sub(left).max(1),
+        bottom.saturating_sub(top).max(1),
+    ))
+}
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    rgba: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: i16, height: i16) -> Self {
+        let width = width.max(1) as usize;
+        let height = height.max(1) as usize;
+        Self {
+            width,
+            height,
+            rgba: vec![0; width * height * 4],
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, [r, g, b]: [u8; 3]) {
+        let index = (y * self.width + x) * 4;
+        self.rgba[index..index + 4].copy_from_slice(&[r, g, b, 0xFF]);
+    }
+
+    /// Draws a rectangle, round rectangle or oval in `area` (x, y, width, height), filled and with an outline
+    fn shape(
+        &mut self,
+        shape_type: ShapeType,
+        area: (i16, i16, i16, i16),
+        fill: Option<[u8; 3]>,
+        line_thickness: u8,
+        line: [u8; 3],
+    ) {
+        let (left, top, width, height) = area;
+        let thickness = f32::from(line_thickness);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // the middle of the pixel, relative to the area
+                let px = x as f32 + 0.5 - f32::from(left);
+                let py = y as f32 + 0.5 - f32::from(top);
+                let inside = |inset: f32| {
+                    inside_shape(
+                        shape_type,
+                        px - inset,
+                        py - inset,
+                        inset.mul_add(-2., f32::from(width)),
+                        inset.mul_add(-2., f32::from(height)),
+                    )
+                };
+                if !inside(0.) {
+                    continue;
+                }
+                if line_thickness > 0 && !inside(thickness) {
+                    self.set(x, y, line);
+                } else if let Some(fill) = fill {
+                    self.set(x, y, fill);
+                }
+            }
+        }
+    }
+
+    /// Draws a line from corner to corner of the canvas
+    fn line(&mut self, up: bool, line_thickness: u8, line: [u8; 3]) {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let (start, end) = if up {
+            ((0., height), (width, 0.))
+        } else {
+            ((0., 0.), (width, height))
+        };
+        let half_thickness = (f32::from(line_thickness) / 2.).max(0.5);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let point = (x as f32 + 0.5, y as f32 + 0.5);
+                if distance_to_segment(point, start, end) <= half_thickness {
+                    self.set(x, y, line);
+                }
+            }
+        }
+    }
+}
+
+fn inside_shape(shape_type: ShapeType, x: f32, y: f32, width: f32, height: f32) -> bool {
+    if width <= 0. || height <= 0. || x < 0. || y < 0. || x >= width || y >= height {
+        return false;
+    }
+    match shape_type {
+        ShapeType::Oval => {
+            let dx = (x - width / 2.) / (width / 2.);
+            let dy = (y - height / 2.) / (height / 2.);
+            dx * dx + dy * dy <= 1.
+        }
+        ShapeType::RoundRectangle => {
+            // only the corners are cut off, by a quarter of a circle
+            let radius = (ROUND_RECT_CORNER / 2.).min(width / 2.).min(height / 2.);
+            let corner_x = x.clamp(radius, width - radius);
+            let corner_y = y.clamp(radius, height - radius);
+            (x - corner_x).hypot(y - corner_y) <= radius
+        }
+        _ => true,
+    }
+}
+
+fn distance_to_segment(point: (f32, f32), start: (f32, f32), end: (f32, f32)) -> f32 {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = dx.hypot(dy);
+    if length == 0. {
+        return (point.0 - start.0).hypot(point.1 - start.1);
+    }
+    let along = (point.0 - start.0).mul_add(dx, (point.1 - start.1) * dy) / (length * length);
+    let along = along.clamp(0., 1.);
+    (point.0 - (start.0 + along * dx)).hypot(point.1 - (start.1 + along * dy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 3] = [0xFF, 0, 0];
+
+    fn palette() -> MacromediaPalette {
+        MacromediaPalette {
+            colors: vec![[0, 0, 0], RED],
+        }
+    }
+
+    fn shape(shape_type: ShapeType, width: i16, height: i16, filled: bool) -> MacromediaShape {
+        MacromediaShape {
+            shape_type,
+            width,
+            height,
+            foreground: 1,
+            filled,
+            line_thickness: 1,
+            line_direction: 0,
+        }
+    }
+
+    fn pixel(rgba: &[u8], width: usize, x: usize, y: usize) -> [u8; 4] {
+        let index = (y * width + x) * 4;
+        rgba[index..index + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn shape_record() {
+        let mut data = vec![0, 1, 3];
+        for value in [10i16, 20, 40, 70] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        // pattern, foreground, background, filled, thickness, direction
+        data.extend_from_slice(&[0, 0x2A, 5, 9, 1, 2, LINE_UP]);
+        let shape = read_shape(&data).unwrap();
+        assert_eq!(shape.shape_type, ShapeType::Oval);
+        assert_eq!((shape.width, shape.height), (50, 30));
+        assert_eq!(shape.foreground, 5);
+        assert!(shape.filled);
+        assert_eq!(shape.line_thickness, 2);
+        assert_eq!(shape.line_direction, LINE_UP);
+
+        data[2] = 9;
+        assert_eq!(read_shape(&data).unwrap().shape_type, ShapeType::Unknown(9));
+        assert!(read_shape(&data[..10]).is_err());
+    }
+
+    #[test]
+    fn button_record() {
+        let mut data = vec![0; TEXT_RECT_OFFSET as usize];
+        for value in [0i16, 0, 16, 80] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.resize(BUTTON_TYPE_OFFSET as usize, 0);
+        data.extend_from_slice(&3u16.to_be_bytes());
+        let button = read_button(&data).unwrap();
+        assert_eq!(button.button_type, ButtonType::RadioButton);
+        assert_eq!((button.width, button.height), (80, 16));
+
+        data[BUTTON_TYPE_OFFSET as usize + 1] = 1;
+        assert_eq!(
+            read_button(&data).unwrap().button_type,
+            ButtonType::PushButton
+        );
+    }
+
+    #[test]
+    fn empty_rect_is_one_pixel() {
+        let mut data = vec![0, 0, 1];
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&[0; 7]);
+        let shape = read_shape(&data).unwrap();
+        assert_eq!((shape.width, shape.height), (1, 1));
+    }
+
+    #[test]
+    fn rectangle() {
+        let outline = shape(ShapeType::Rectangle, 4, 3, false).rasterize(&palette());
+        assert_eq!(outline.len(), 4 * 3 * 4);
+        assert_eq!(pixel(&outline, 4, 0, 0), [0xFF, 0, 0, 0xFF]);
+        assert_eq!(pixel(&outline, 4, 3, 2), [0xFF, 0, 0, 0xFF]);
+        assert_eq!(pixel(&outline, 4, 1, 1), [0; 4]);
+        assert_eq!(pixel(&outline, 4, 2, 1), [0; 4]);
+
+        let filled = shape(ShapeType::Rectangle, 4, 3, true).rasterize(&palette());
+        assert!(filled.chunks(4).all(|pixel| pixel == [0xFF, 0, 0, 0xFF]));
+    }
+
+    #[test]
+    fn oval() {
+        let oval = shape(ShapeType::Oval, 9, 9, true).rasterize(&palette());
+        for (x, y) in [(0, 0), (8, 0), (0, 8), (8, 8)] {
+            assert_eq!(pixel(&oval, 9, x, y), [0; 4], "corner {x}, {y}");
+        }
+        for (x, y) in [(4, 4), (4, 0), (0, 4), (8, 4), (4, 8)] {
+            assert_eq!(pixel(&oval, 9, x, y), [0xFF, 0, 0, 0xFF], "{x}, {y}");
+        }
+
+        let ring = shape(ShapeType::Oval, 9, 9, false).rasterize(&palette());
+        assert_eq!(pixel(&ring, 9, 4, 0), [0xFF, 0, 0, 0xFF]);
+        assert_eq!(pixel(&ring, 9, 4, 4), [0; 4]);
+    }
+
+    #[test]
+    fn line() {
+        let mut down = shape(ShapeType::Line, 4, 4, false);
+        let rgba = down.rasterize(&palette());
+        for i in 0..4 {
+            assert_eq!(pixel(&rgba, 4, i, i), [0xFF, 0, 0, 0xFF]);
+        }
+        assert_eq!(pixel(&rgba, 4, 3, 0), [0; 4]);
+        assert_eq!(pixel(&rgba, 4, 0, 3), [0; 4]);
+
+        down.line_direction = LINE_UP;
+        let rgba = down.rasterize(&palette());
+        for i in 0..4 {
+            assert_eq!(pixel(&rgba, 4, i, 3 - i), [0xFF, 0, 0, 0xFF]);
+        }
+        assert_eq!(pixel(&rgba, 4, 0, 0), [0; 4]);
+    }
+
+    #[test]
+    fn check_box_is_drawn_left() {
+        let button = MacromediaButton {
+            button_type: ButtonType::CheckBox,
+            width: 40,
+            height: 12,
+        };
+        let rgba = button.rasterize();
+        assert_eq!(pixel(&rgba, 40, 0, 0), [0, 0, 0, 0xFF]);
+        assert_eq!(pixel(&rgba, 40, 5, 5), [0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(pixel(&rgba, 40, 20, 5), [0; 4]);
+    }
+}
@@ -0,0 +1,405 @@
+use std::io::{self, Cursor};
+
+use super::{ChunkReader, Endianness};
+
+// Sound members carry a classic Mac sound, either as a whole `snd ` resource
+// or split into a sndH header and the raw sndS samples (director 6 and up)
+// Everything in here is big endian, like the Mac it came from
+
+// sound commands that point at a sound header
+const SOUND_CMD: u16 = 0x50;
+const BUFFER_CMD: u16 = 0x51;
+
+// the encode byte of a sound header
+const STANDARD_HEADER: u8 = 0x00;
+const COMPRESSED_HEADER: u8 = 0xFE;
+const EXTENDED_HEADER: u8 = 0xFF;
+
+const IMA4_PACKET_SIZE: usize = 34;
+const IMA4_SAMPLES_PER_PACKET: usize = 64;
+
+const IMA_INDEX_TABLE: [i8; 16] = [-1, -1, -1, -1, 2, 4, 6, 8, -1, -1, -1, -1, 2, 4, 6, 8];
+const IMA_STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+    73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449,
+    494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272,
+    2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493,
+    10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
+];
+
+/// Decoded sound, the samples are interleaved when there is more than one channel
+#[derive(Clone, Debug, Default)]
+pub struct MacromediaSound {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<i16>,
+}
+
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub struct MacromediaCuePoint {
+    pub time: u32,
+    pub name: String,
+}
+
+enum SampleEncoding {
+    Unsigned8,
+    Signed16,
+    Ima4,
+    Mace(u8),
+    Unknown([u8; 4]),
+}
+
+struct SoundHeader {
+    sample_rate: u32,
+    channels: u16,
+    frames: u32,
+    encoding: SampleEncoding,
+}
+
+/// Decodes a complete `snd ` resource, the samples follow the header inside the resource
+pub fn read_snd(data: &[u8]) -> io::Result<MacromediaSound> {
+    let mut resource = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+
+    match resource.u16()? {
+        1 => {
+            let modifiers = resource.u16()?;
+            resource.skip(i64::from(modifiers) * 6)?; // modifier number and init options
+        }
+        2 => {
+            resource.skip(2)?; // reference count
+        }
+        format => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown snd resource format {format}"),
+            ))
+        }
+    }
+
+    let commands = resource.u16()?;
+    for _ in 0..commands {
+        let command = resource.u16()? & 0x7FFF; // the high bit marks param2 as an offset
+        let _param1 = resource.u16()?;
+        let param2 = resource.u32()?;
+
+        if command == SOUND_CMD || command == BUFFER_CMD {
+            resource.seek(u64::from(param2))?;
+            let header = read_sound_header(&mut resource)?;
+            let remaining = resource.remaining()? as usize;
+            let samples = resource.bytes(remaining)?;
+            return decode_samples(&header, &samples);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "snd resource has no sound command",
+    ))
+}
+
+/// Decodes a sound that was split into a sndH header and sndS samples
+/// the header is the same sound header the `snd ` resource carries, without any samples after it
+pub fn read_split(header: &[u8], samples: &[u8]) -> io::Result<MacromediaSound> {
+    let mut header_reader = ChunkReader::new(Cursor::new(header), Endianness::Big)?;
+    let header = read_sound_header(&mut header_reader)?;
+    decode_samples(&header, samples)
+}
+
+/// The cue points of a sound member, the time is in milliseconds
+pub fn read_cue_points(data: &[u8]) -> io::Result<Vec<MacromediaCuePoint>> {
+    let mut cupt = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+    let amount = cupt.u32()?;
+
+    let mut cue_points = Vec::new();
+    for _ in 0..amount {
+        let time = cupt.u32()?;
+        let mut name_field = ChunkReader::new(Cursor::new(cupt.bytes(32)?), Endianness::Big)?;
+        cue_points.push(MacromediaCuePoint {
+            time,
+            name: name_field.pascal_string()?,
+        });
+    }
+    Ok(cue_points)
+}
+
+fn read_sound_header<R: io::Read + io::Seek>(
+    reader: &mut ChunkReader<R>,
+) -> io::Result<SoundHeader> {
+    let _sample_pointer = reader.u32()?;
+    let length_or_channels = reader.u32()?;
+    let sample_rate = reader.u32()? >> 16; // unsigned 16.16 fixed point
+    let _loop_start = reader.u32()?;
+    let _loop_end = reader.u32()?;
+    let encode = reader.u8()?;
+    let _base_frequency = reader.u8()?;
+
+    match encode {
+        STANDARD_HEADER => Ok(SoundHeader {
+            sample_rate,
+            channels: 1,
+            frames: length_or_channels,
+            encoding: SampleEncoding::Unsigned8,
+        }),
+        EXTENDED_HEADER => {
+            let frames = reader.u32()?;
+            reader.skip(10 + 4 + 4 + 4)?; // AIFF sample rate, marker chunk, instrument chunks, AES recording
+            let sample_size = reader.u16()?;
+            reader.skip(2 + 4 + 4 + 4)?; // future use
+            Ok(SoundHeader {
+                sample_rate,
+                channels: length_or_channels as u16,
+                frames,
+                encoding: if sample_size == 16 {
+                    SampleEncoding::Signed16
+                } else {
+                    SampleEncoding::Unsigned8
+                },
+            })
+        }
+        COMPRESSED_HEADER => {
+            let frames = reader.u32()?;
+            reader.skip(10 + 4)?; // AIFF sample rate, marker chunk
+            let format = reader.fourcc()?;
+            reader.skip(4 + 4 + 4)?; // future use, state vars, left over samples
+            let compression_id = reader.i16()?;
+            let _packet_size = reader.u16()?;
+            let _synth_id = reader.u16()?;
+            let sample_size = reader.u16()?;
+
+            let encoding = match (&format, compression_id) {
+                (b"ima4", _) => SampleEncoding::Ima4,
+                (b"MAC3", _) | (_, 3) => SampleEncoding::Mace(3),
+                (b"MAC6", _) | (_, 4) => SampleEncoding::Mace(6),
+                (b"twos", _) => SampleEncoding::Signed16,
+                (b"raw ", _) => SampleEncoding::Unsigned8,
+                (_, 0 | -1) if sample_size == 16 => SampleEncoding::Signed16,
+                (_, 0 | -1) => SampleEncoding::Unsigned8,
+                _ => SampleEncoding::Unknown(format),
+            };
+            Ok(SoundHeader {
+                sample_rate,
+                channels: length_or_channels as u16,
+                frames,
+                encoding,
+            })
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unknown sound header encoding {encode:#x}"),
+        )),
+    }
+}
+
+fn decode_samples(header: &SoundHeader, data: &[u8]) -> io::Result<MacromediaSound> {
+    let channels = header.channels.max(1);
+    let sample_count = header.frames as usize * channels as usize;
+
+    let samples = match header.encoding {
+        SampleEncoding::Unsigned8 => data
+            .iter()
+            .take(sample_count)
+            .map(|sample| (i16::from(*sample) - 128) << 8)
+            .collect(),
+        SampleEncoding::Signed16 => data
+            .chunks_exact(2)
+            .take(sample_count)
+            .map(|sample| i16::from_be_bytes([sample[0], sample[1]]))
+            .collect(),
+        SampleEncoding::Ima4 => decode_ima4(data, header.frames as usize, channels as usize),
+        // not done yet, the decoder needs the tables of Apple's MACE and we have no reference for them to check against
+        // the sounds are skipped with this error instead of playing noise
+        SampleEncoding::Mace(ratio) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("MACE {ratio}:1 compressed sound"),
+            ))
+        }
+        SampleEncoding::Unknown(format) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("sound compression {:?}", String::from_utf8_lossy(&format)),
+            ))
+        }
+    };
+
+    Ok(MacromediaSound {
+        sample_rate: header.sample_rate,
+        channels,
+        samples,
+    })
+}
+
+// Apple IMA4 packs 64 samples per channel in 34 byte packets, the packets of each channel take turns
+fn decode_ima4(data: &[u8], packets: usize, channels: usize) -> Vec<i16> {
+    let mut samples = vec![0i16; packets * IMA4_SAMPLES_PER_PACKET * channels];
+
+    for (packet_number, packet) in data
+        .chunks_exact(IMA4_PACKET_SIZE)
+        .take(packets * channels)
+        .enumerate()
+    {
+        let channel = packet_number % channels;
+        let first_frame = (packet_number / channels) * IMA4_SAMPLES_PER_PACKET;
+
+        let preamble = u16::from_be_bytes([packet[0], packet[1]]);
+        let mut predictor = i32::from((preamble & 0xFF80) as i16);
+        let mut step_index = (preamble & 0x7F).min(88) as usize;
+
+        let nibbles = packet[2..].iter().flat_map(|byte| [byte & 0x0F, byte >> 4]);
+        for (frame, nibble) in nibbles.enumerate() {
+            let step = IMA_STEP_TABLE[step_index];
+            let mut diff = step >> 3;
+            if nibble & 1 != 0 {
+                diff += step >> 2;
+            }
+            if nibble & 2 != 0 {
+                diff += step >> 1;
+            }
+            if nibble & 4 != 0 {
+                diff += step;
+            }
+            if nibble & 8 != 0 {
+                diff = -diff;
+            }
+            predictor = (predictor + diff).clamp(i32::from(i16::MIN), i32::from(i16::MAX));
+            step_index = (step_index as i32 + i32::from(IMA_INDEX_TABLE[nibble as usize]))
+                .clamp(0, 88) as usize;
+
+            samples[(first_frame + frame) * channels + channel] = predictor as i16;
+        }
+    }
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the sound header up to the encode byte, 22050 Hz
+    fn header(length_or_channels: u32, encode: u8) -> Vec<u8> {
+        let mut header = vec![0; 4];
+        header.extend_from_slice(&length_or_channels.to_be_bytes());
+        header.extend_from_slice(&0x5622_0000u32.to_be_bytes());
+        header.extend_from_slice(&[0; 8]); // loop
+        header.extend_from_slice(&[encode, 0x3C]);
+        header
+    }
+
+    fn compressed_header(
+        channels: u32,
+        frames: u32,
+        format: &[u8; 4],
+        compression_id: i16,
+    ) -> Vec<u8> {
+        let mut header = header(channels, COMPRESSED_HEADER);
+        header.extend_from_slice(&frames.to_be_bytes());
+        header.extend_from_slice(&[0; 14]); // AIFF sample rate, marker chunk
+        header.extend_from_slice(format);
+        header.extend_from_slice(&[0; 12]);
+        header.extend_from_slice(&compression_id.to_be_bytes());
+        header.extend_from_slice(&[0, 0, 0, 0, 0, 16]); // packet size, synth and sample size
+        header
+    }
+
+    #[test]
+    fn reads_a_format_1_resource() {
+        let mut snd = vec![0, 1, 0, 1, 0, 5, 0, 0, 0, 0x80]; // one modifier, sampled synth
+        snd.extend_from_slice(&[0, 1, 0x80, 0x51, 0, 0, 0, 0, 0, 20]); // one buffer command with an offset
+        snd.extend_from_slice(&header(3, STANDARD_HEADER));
+        snd.extend_from_slice(&[0x80, 0xFF, 0x00, 0x42]); // one sample more than the length says
+
+        let sound = read_snd(&snd).unwrap();
+        assert_eq!(sound.sample_rate, 22050);
+        assert_eq!(sound.channels, 1);
+        assert_eq!(sound.samples, [0, 127 << 8, -128 << 8]);
+    }
+
+    #[test]
+    fn reads_a_format_2_resource() {
+        let mut snd = vec![0, 2, 0, 0, 0, 1, 0, 0x50, 0, 0, 0, 0, 0, 14];
+        snd.extend_from_slice(&header(1, STANDARD_HEADER));
+        snd.push(0x90);
+        assert_eq!(read_snd(&snd).unwrap().samples, [16 << 8]);
+
+        assert!(read_snd(&[0, 3, 0, 0]).is_err());
+        // no sound command
+        assert!(read_snd(&[0, 2, 0, 0, 0, 1, 0, 0x2C, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn reads_an_extended_header() {
+        let mut header = header(2, EXTENDED_HEADER);
+        header.extend_from_slice(&2u32.to_be_bytes());
+        header.extend_from_slice(&[0; 22]);
+        header.extend_from_slice(&16u16.to_be_bytes());
+        header.extend_from_slice(&[0; 14]);
+
+        let samples = [0x12, 0x34, 0xFF, 0xFE, 0x00, 0x01, 0x80, 0x00];
+        let sound = read_split(&header, &samples).unwrap();
+        assert_eq!(sound.channels, 2);
+        assert_eq!(sound.samples, [0x1234, -2, 1, i16::MIN]);
+    }
+
+    #[test]
+    fn reads_compressed_headers() {
+        let twos = compressed_header(1, 1, b"twos", 0);
+        assert_eq!(read_split(&twos, &[0x01, 0x00]).unwrap().samples, [256]);
+
+        for (format, compression_id) in [(b"MAC3", 0), (b"\0\0\0\0", 4)] {
+            let mace = compressed_header(1, 1, format, compression_id);
+            let error = read_split(&mace, &[0; 2]).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        }
+        let unknown = compressed_header(1, 1, b"ulaw", 2);
+        assert_eq!(
+            read_split(&unknown, &[0; 2]).unwrap_err().kind(),
+            io::ErrorKind::Unsupported
+        );
+    }
+
+    #[test]
+    fn decodes_an_ima4_packet() {
+        let header = compressed_header(1, 1, b"ima4", -1);
+        let mut packet = vec![0x01, 0x00]; // predictor 256, step index 0
+        packet.extend_from_slice(&[0x77, 0x8F]);
+        packet.resize(IMA4_PACKET_SIZE, 0);
+
+        let sound = read_split(&header, &packet).unwrap();
+        assert_eq!(sound.samples.len(), IMA4_SAMPLES_PER_PACKET);
+        // step 7 and nibble 7: 7/8 + 7/4 + 7/2 + 7 = 11 and the step index goes up to 8,
+        // step 16 and nibble 7 adds 30 and goes up to index 16,
+        // step 34 and nibble 0xF takes 4 + 8 + 17 + 34 off and goes up to 24,
+        // step 73 and nibble 8 only takes 73 / 8 off and goes down to 23
+        assert_eq!(sound.samples[..4], [267, 297, 234, 225]);
+        // the zero nibbles after that add step / 8 and lower the step index
+        assert_eq!(sound.samples[4], 225 + 66 / 8);
+    }
+
+    #[test]
+    fn interleaves_ima4_channels() {
+        let header = compressed_header(2, 1, b"ima4", -1);
+        let mut data = vec![0; 2 * IMA4_PACKET_SIZE];
+        data[0] = 0x10; // left starts at 4096
+        data[IMA4_PACKET_SIZE] = 0xF0; // right at -4096
+        let samples = read_split(&header, &data).unwrap().samples;
+        assert_eq!(samples.len(), 2 * IMA4_SAMPLES_PER_PACKET);
+        assert_eq!(samples[..2], [4096, -4096]);
+        // zero nibbles at the smallest step add nothing
+        assert!(samples.chunks_exact(2).all(|frame| frame == [4096, -4096]));
+    }
+
+    #[test]
+    fn reads_cue_points() {
+        let mut cupt = 1u32.to_be_bytes().to_vec();
+        cupt.extend_from_slice(&1500u32.to_be_bytes());
+        let mut name = vec![4];
+        name.extend_from_slice(b"beat");
+        name.resize(32, 0);
+        cupt.extend_from_slice(&name);
+
+        let cue_points = read_cue_points(&cupt).unwrap();
+        assert_eq!(cue_points.len(), 1);
+        assert_eq!(cue_points[0].time, 1500);
+        assert_eq!(cue_points[0].name, "beat");
+    }
+}
@@ -0,0 +1,333 @@
+use std::{
+    collections::HashMap,
+    io::{self, Cursor},
+};
+
+use serde::{Deserialize, Serialize};
+use yore::code_pages::CP1252;
+
+use super::{ChunkReader, Endianness};
+
+// Text and field members (type 3) keep their text in an STXT chunk, followed by a table of style runs
+// every run sets the font, size, style and colour from its offset up to the next run
+// The runs only have a font number, the movie maps those to names in its VWFM (up to director 5) or Fmap chunk
+// Rich text members (type 12) keep a styled copy (RTE0), the plain text (RTE1) and a prerendered bitmap (RTE2),
+// only the plain text is read. The styled text of the text xtra (XMED) is not decoded yet
+
+// underline (0x04) is left out, bevy text can not draw it
+const STYLE_BOLD: u8 = 0x01;
+const STYLE_ITALIC: u8 = 0x02;
+
+const RUN_SIZE: u64 = 20;
+
+/// The style of the text from `start` up to the start of the next run
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacromediaTextRun {
+    /// Offset into the text, in characters
+    pub start: u32,
+    /// Look this up in the font map of the movie
+    pub font_id: u16,
+    pub bold: bool,
+    pub italic: bool,
+    pub size: u16,
+    pub color: [u8; 3],
+}
+
+impl Default for MacromediaTextRun {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            font_id: 0,
+            bold: false,
+            italic: false,
+            size: 12,
+            color: [0, 0, 0],
+        }
+    }
+}
+
+/// The text of a member and how it is styled, director uses \r for line breaks
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MacromediaText {
+    pub text: String,
+    pub runs: Vec<MacromediaTextRun>,
+}
+
+impl MacromediaText {
+    /// Text without styling, it gets a single run in the default style
+    pub fn plain(text: String) -> Self {
+        Self {
+            text,
+            runs: vec![MacromediaTextRun::default()],
+        }
+    }
+}
+
+/// The text cut up along the runs, with line breaks turned into \n
+pub fn spans<'a>(
+    text: &str,
+    runs: &'a [MacromediaTextRun],
+) -> Vec<(String, &'a MacromediaTextRun)> {
+    let characters: Vec<char> = text.chars().collect();
+    runs.iter()
+        .enumerate()
+        .filter_map(|(index, run)| {
+            let start = (run.start as usize).min(characters.len());
+            let end = runs
+                .get(index + 1)
+                .map_or(characters.len(), |next| next.start as usize)
+                .clamp(start, characters.len());
+            let span: String = characters[start..end]
+                .iter()
+                .map(|character| if *character == '\r' { '\n' } else { *character })
+                .collect();
+            (!span.is_empty()).then_some((span, run))
+        })
+        .collect()
+}
+
+pub fn read_stxt(data: &[u8]) -> io::Result<MacromediaText> {
+    let mut stxt = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+    let header_length = stxt.u32()?;
+    let text_length = stxt.u32()?;
+    let _runs_length = stxt.u32()?;
+
+    stxt.seek(u64::from(header_length))?;
+    let text = CP1252
+        .decode(&stxt.bytes(text_length as usize)?)
+        .to_string();
+
+    let mut runs = Vec::new();
+    // very old fields can end right after the text
+    if stxt.remaining()? >= 2 {
+        let runs_count = stxt.u16()?;
+        let runs_start = stxt.position()?;
+        for index in 0..u64::from(runs_count) {
+            stxt.seek(runs_start + index * RUN_SIZE)?;
+            runs.push(read_run(&mut stxt)?);
+        }
+    }
+    if runs.is_empty() {
+        return Ok(MacromediaText::plain(text));
+    }
+    Ok(MacromediaText { text, runs })
+}
+
+fn read_run(stxt: &mut ChunkReader<Cursor<&[u8]>>) -> io::Result<MacromediaTextRun> {
+    let start = stxt.u32()?;
+    stxt.skip(4)?; // line height and ascent, bevy picks the line height from the font size
+    let font_id = stxt.u16()?;
+    let style = stxt.u8()?;
+    stxt.skip(1)?; // unknown
+    let size = stxt.u16()?;
+    // 16 bit colour channels of which only the high byte matters
+    let [r, _] = stxt.u16()?.to_be_bytes();
+    let [g, _] = stxt.u16()?.to_be_bytes();
+    let [b, _] = stxt.u16()?.to_be_bytes();
+    Ok(MacromediaTextRun {
+        start,
+        font_id,
+        bold: style & STYLE_BOLD != 0,
+        italic: style & STYLE_ITALIC != 0,
+        size,
+        color: [r, g, b],
+    })
+}
+
+/// The plain text of a rich text member, it ends at the first null
+pub fn read_rte1(data: &[u8]) -> MacromediaText {
+    let end = data
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(data.len());
+    MacromediaText::plain(CP1252.decode(&data[..end]).to_string())
+}
+
+/// Font names by font number from a VWFM chunk, a list of numbers followed by their names
+pub fn read_vwfm(data: &[u8]) -> io::Result<HashMap<u16, String>> {
+    let mut vwfm = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+    let count = vwfm.u16()?;
+    let font_ids = (0..count)
+        .map(|_| vwfm.u16())
+        .collect::<io::Result<Vec<u16>>>()?;
+    font_ids
+        .into_iter()
+        .map(|font_id| Ok((font_id, vwfm.pascal_string()?)))
+        .collect()
+}
+
+/// Font names by font number from an Fmap chunk, a table of entries followed by a block of names
+pub fn read_fmap(data: &[u8]) -> io::Result<HashMap<u16, String>> {
+    let mut fmap = ChunkReader::new(Cursor::new(data), Endianness::Big)?;
+    let map_length = fmap.u32()?;
+    let _names_length = fmap.u32()?;
+    let names_start = 8 + u64::from(map_length);
+    fmap.skip(8)?; // unknown
+    let entries_used = fmap.u32()?;
+    fmap.skip(4 + 12)?; // entries allocated and unknown
+
+    let mut fonts = HashMap::new();
+    for index in 0..u64::from(entries_used) {
+        fmap.seek(36 + index * 8)?;
+        let name_offset = fmap.u32()?;
+        let _platform = fmap.u16()?;
+        let font_id = fmap.u16()?;
+
+        fmap.seek(names_start + u64::from(name_offset))?;
+        let name_length = fmap.u32()?;
+        let name = CP1252
+            .decode(&fmap.bytes(name_length as usize)?)
+            .to_string();
+        fonts.insert(font_id, name);
+    }
+    Ok(fonts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(start: u32, font_id: u16, style: u8, size: u16, color: [u8; 3]) -> Vec<u8> {
+        let mut run = start.to_be_bytes().to_vec();
+        run.extend_from_slice(&[0, 14, 0, 11]); // height and ascent
+        run.extend_from_slice(&font_id.to_be_bytes());
+        run.extend_from_slice(&[style, 0]);
+        run.extend_from_slice(&size.to_be_bytes());
+        for channel in color {
+            run.extend_from_slice(&[channel, channel]);
+        }
+        run
+    }
+
+    fn stxt(text: &[u8], runs: &[Vec<u8>]) -> Vec<u8> {
+        let runs_length = 2 + runs.len() * 20;
+        let mut stxt = vec![0, 0, 0, 12];
+        stxt.extend_from_slice(&(text.len() as u32).to_be_bytes());
+        stxt.extend_from_slice(&(runs_length as u32).to_be_bytes());
+        stxt.extend_from_slice(text);
+        stxt.extend_from_slice(&(runs.len() as u16).to_be_bytes());
+        for run in runs {
+            stxt.extend_from_slice(run);
+        }
+        stxt
+    }
+
+    #[test]
+    fn reads_text_and_runs() {
+        let data = stxt(
+            b"Hej Mulle\r\xE5ka",
+            &[
+                run(0, 3, STYLE_BOLD, 12, [0, 0, 0]),
+                run(4, 5, STYLE_ITALIC | 0x04, 18, [0xFF, 0, 0x80]),
+            ],
+        );
+        let text = read_stxt(&data).unwrap();
+        assert_eq!(text.text, "Hej Mulle\råka");
+        assert_eq!(text.runs.len(), 2);
+        assert_eq!(
+            text.runs[1],
+            MacromediaTextRun {
+                start: 4,
+                font_id: 5,
+                bold: false,
+                italic: true,
+                size: 18,
+                color: [0xFF, 0, 0x80],
+            }
+        );
+        assert!(text.runs[0].bold && !text.runs[0].italic);
+    }
+
+    #[test]
+    fn text_without_runs_is_plain() {
+        let mut data = vec![0, 0, 0, 12, 0, 0, 0, 2, 0, 0, 0, 0];
+        data.extend_from_slice(b"ok");
+        let text = read_stxt(&data).unwrap();
+        assert_eq!(text.text, "ok");
+        assert_eq!(text.runs, [MacromediaTextRun::default()]);
+
+        assert_eq!(read_stxt(&stxt(b"ok", &[])).unwrap().runs.len(), 1);
+        // the text is longer than the chunk
+        assert!(read_stxt(&[0, 0, 0, 12, 0, 0, 0, 9, 0, 0, 0, 0, b'o']).is_err());
+    }
+
+    #[test]
+    fn spans_follow_the_runs() {
+        let runs = [
+            MacromediaTextRun::default(),
+            MacromediaTextRun {
+                start: 4,
+                bold: true,
+                ..MacromediaTextRun::default()
+            },
+            // past the end of the text, nothing is left for it
+            MacromediaTextRun {
+                start: 40,
+                ..MacromediaTextRun::default()
+            },
+        ];
+        let cut = spans("Hej\rMulle", &runs);
+        assert_eq!(cut.len(), 2);
+        assert_eq!(cut[0].0, "Hej\n");
+        assert!(!cut[0].1.bold);
+        assert_eq!(cut[1].0, "Mulle");
+        assert!(cut[1].1.bold);
+
+        // runs count characters and not bytes
+        let runs = [
+            MacromediaTextRun::default(),
+            MacromediaTextRun {
+                start: 2,
+                ..MacromediaTextRun::default()
+            },
+        ];
+        let cut = spans("åäö", &runs);
+        assert_eq!(cut[0].0, "åä");
+        assert_eq!(cut[1].0, "ö");
+    }
+
+    #[test]
+    fn rich_text_ends_at_the_first_null() {
+        assert_eq!(read_rte1(b"Bil\xE4\0junk").text, "Bilä");
+        assert_eq!(read_rte1(b"no null").text, "no null");
+    }
+
+    #[test]
+    fn reads_vwfm_font_names() {
+        let mut vwfm = vec![0, 2, 0, 3, 0, 16];
+        vwfm.push(6);
+        vwfm.extend_from_slice(b"Geneva");
+        vwfm.push(5);
+        vwfm.extend_from_slice(b"Arial");
+        let fonts = read_vwfm(&vwfm).unwrap();
+        assert_eq!(fonts.len(), 2);
+        assert_eq!(fonts[&3], "Geneva");
+        assert_eq!(fonts[&16], "Arial");
+        assert!(read_vwfm(&[0, 2, 0, 3]).is_err());
+    }
+
+    #[test]
+    fn reads_fmap_font_names() {
+        // 36 bytes of header and two 8 byte entries, the names follow
+        let map_length: u32 = 28 + 2 * 8;
+        let mut fmap = map_length.to_be_bytes().to_vec();
+        fmap.extend_from_slice(&18u32.to_be_bytes());
+        fmap.extend_from_slice(&[0; 8]);
+        fmap.extend_from_slice(&2u32.to_be_bytes());
+        fmap.extend_from_slice(&[0; 16]);
+        for (name_offset, font_id) in [(0u32, 3u16), (9, 7)] {
+            fmap.extend_from_slice(&name_offset.to_be_bytes());
+            fmap.extend_from_slice(&[0, 1]); // platform
+            fmap.extend_from_slice(&font_id.to_be_bytes());
+        }
+        for name in [&b"Arial"[..], b"Geneva"] {
+            fmap.extend_from_slice(&(name.len() as u32).to_be_bytes());
+            fmap.extend_from_slice(name);
+        }
+
+        let fonts = read_fmap(&fmap).unwrap();
+        assert_eq!(fonts.len(), 2);
+        assert_eq!(fonts[&3], "Arial");
+        assert_eq!(fonts[&7], "Geneva");
+    }
+}
@@ -7,14 +7,17 @@ use bevy::{
         },
         view::RenderLayers,
     },
-    window::WindowResized,
+    window::{PrimaryWindow, WindowResized},
 };
 
+use crate::systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetState};
+
 pub struct ScalerPlugin;
 
 impl Plugin for ScalerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_camera)
+            .add_systems(OnEnter(MulleAssetState::Loaded), resize_stage)
             .add_systems(Update, fit_canvas);
     }
 }
@@ -23,12 +26,19 @@ impl Plugin for ScalerPlugin {
 #[derive(Component)]
 pub struct InGameCamera;
 
-/// In-game resolution width.
+/// In-game resolution width when no movie has a config.
 const RES_WIDTH: u32 = 640;
 
-/// In-game resolution height.
+/// In-game resolution height when no movie has a config.
 const RES_HEIGHT: u32 = 480;
 
+/// In-game resolution, the size of the stage of the movies.
+#[derive(Resource, Clone, Copy)]
+pub struct StageSize {
+    pub width: u32,
+    pub height: u32,
+}
+
 /// Camera that renders the [`Canvas`] (and other graphics on [`HIGH_RES_LAYERS`]) to the screen.
 #[derive(Component)]
 pub struct OuterCamera;
@@ -36,20 +46,66 @@ pub struct OuterCamera;
 /// Scales camera projection to fit the window (integer multiples only).
 pub fn fit_canvas(
     mut resize_events: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
     mut projections: Query<&mut OrthographicProjection, With<OuterCamera>>,
+    stage: Res<StageSize>,
 ) {
-    for event in resize_events.read() {
-        let h_scale = event.width / RES_WIDTH as f32;
-        let v_scale = event.height / RES_HEIGHT as f32;
+    // the stage gets its size once the movies are loaded, the window has to be fitted again then
+    let stage_resized = stage
+        .is_changed()
+        .then(|| windows.get_single().ok())
+        .flatten()
+        .map(|window| (window.width(), window.height()));
+    let sizes = resize_events
+        .read()
+        .map(|event| (event.width, event.height))
+        .chain(stage_resized);
+    for (width, height) in sizes {
+        let h_scale = width / stage.width as f32;
+        let v_scale = height / stage.height as f32;
         let mut projection = projections.single_mut();
         projection.scale = 1. / h_scale.min(v_scale);
     }
 }
 
+/// Gives the canvas the size of the stage of the movies, until then it has the fallback size
+fn resize_stage(
+    mut stage: ResMut<StageSize>,
+    mut images: ResMut<Assets<Image>>,
+    canvases: Query<&Sprite, With<Canvas>>,
+    mulle_asset_helper: Res<MulleAssetHelp>,
+) {
+    let Some(config) = mulle_asset_helper
+        .stage_config()
+        .filter(|config| config.stage_width() > 0 && config.stage_height() > 0)
+    else {
+        return;
+    };
+    *stage = StageSize {
+        width: u32::from(config.stage_width()),
+        height: u32::from(config.stage_height()),
+    };
+    for canvas in &canvases {
+        if let Some(image) = images.get_mut(&canvas.image) {
+            image.resize(Extent3d {
+                width: stage.width,
+                height: stage.height,
+                ..default()
+            });
+        }
+    }
+}
+
 pub fn setup_camera(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
-    let canvas_size = Extent3d {
+    let stage = StageSize {
         width: RES_WIDTH,
         height: RES_HEIGHT,
+    };
+    commands.insert_resource(stage);
+
+    let canvas_size = Extent3d {
+        width: stage.width,
+        height: stage.height,
         ..default()
     };
 
@@ -1,8 +1,4 @@
-use crate::render::scaler::PIXEL_PERFECT_LAYERS;
-use crate::systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper};
-use crate::systems::mulle_point_and_click::{
-    deploy_clickables, mulle_clickable_from_name, ClickAction, MulleClickable,
-};
+use crate::systems::mulle_asset_helper::MulleAssetHelp;
 use crate::{despawn_screen, GameState};
 use bevy::prelude::*;
 
@@ -28,4 +24,5 @@ impl Plugin for GaragePlugin {
 #[derive(Component, Clone)]
 struct OnGarageScreen;
 
-fn setup_garage(mut commands: Commands, mulle_asset_helper: Res<MulleAssetHelp>) {}
+#[allow(clippy::missing_const_for_fn)]
+fn setup_garage(_commands: Commands, _mulle_asset_helper: Res<MulleAssetHelp>) {}
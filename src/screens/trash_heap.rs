@@ -1,3 +1,4 @@
+#![allow(dead_code, unused_imports)] // parked until the trash heaps are rooms
 use crate::render::scaler::PIXEL_PERFECT_LAYERS;
 use crate::systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper};
 use crate::systems::mulle_point_and_click::{
@@ -7,18 +7,24 @@ use yore::code_pages::CP1252;
 
 use crate::parsers::database_language::MapData;
 use crate::render::scaler::{HIGH_RES_LAYERS, PIXEL_PERFECT_LAYERS};
-use crate::systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper};
+use crate::systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper, MulleAssetState};
+use crate::systems::mulle_locale::Locale;
 use crate::{despawn_screen, GameState};
 
+// the maps and their collision masks are found through the casts the driving movie links
+const DRIVE_MOVIE: &str = "05.dxr";
+
 pub struct WorldDrivePlugin;
 
 impl Plugin for WorldDrivePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init_maps)
+        app.add_systems(OnEnter(MulleAssetState::Loaded), init_maps)
             .add_systems(OnEnter(GameState::DaHood), setup_sprite)
             .add_systems(OnExit(GameState::DaHood), despawn_screen::<OnWorldDrive>)
-            .add_systems(Update, update_map)
-            .add_systems(Update, control_car);
+            .add_systems(
+                Update,
+                (update_map, control_car).run_if(in_state(MulleAssetState::Loaded)),
+            );
     }
 }
 
@@ -33,7 +39,7 @@ fn update_map(
         for mut image_handle in &mut query {
             *image_handle = mulle_asset_helper
                 .get_image_by_name(
-                    "cddata.cxt".to_string(),
+                    DRIVE_MOVIE.to_string(),
                     da_hood
                         .maps
                         .get(&car_state.current_map)
@@ -167,6 +173,7 @@ fn setup_sprite(
     mulle_asset_helper: Res<MulleAssetHelp>,
     da_hood: Res<MulleWorldData>,
     car_state: Res<MulleCarState>,
+    locale: Res<Locale>,
 ) {
     // Maybe have these only created once?
 
@@ -174,7 +181,7 @@ fn setup_sprite(
     commands.spawn((
         mulle_asset_helper
             .get_image_by_name(
-                "cddata.cxt".to_string(),
+                DRIVE_MOVIE.to_string(),
                 da_hood
                     .maps
                     .get(&car_state.current_map)
@@ -193,7 +200,7 @@ fn setup_sprite(
 
     commands.spawn((
         mulle_asset_helper
-            .get_image_by_asset_number("05.dxr".to_string(), 25)
+            .get_image_by_asset_number(DRIVE_MOVIE.to_string(), locale.member(DRIVE_MOVIE, 25))
             .unwrap()
             .clone(),
         Transform::from_xyz(0., -198., 0.),
@@ -204,7 +211,7 @@ fn setup_sprite(
     // the sample sprite that will be rendered to the high-res "outer world"
     commands.spawn((
         mulle_asset_helper
-            .get_image_by_asset_number("05.dxr".to_string(), 101)
+            .get_image_by_asset_number(DRIVE_MOVIE.to_string(), locale.member(DRIVE_MOVIE, 101))
             .unwrap()
             .clone(),
         Transform::from_xyz(5., 30., 2.),
@@ -225,7 +232,7 @@ fn store_colission_mask(
         CP1252
             .encode(
                 mulle_asset_helper
-                    .get_mulle_text_by_name("cddata.cxt".to_string(), asset_name.to_string())
+                    .get_mulle_text_by_name(DRIVE_MOVIE.to_string(), asset_name.to_string())
                     .unwrap()
                     .text
                     .as_str(),
@@ -236,7 +243,7 @@ fn store_colission_mask(
         CP1252
             .encode(
                 mulle_asset_helper
-                    .get_mulle_text_by_name("cddata.cxt".to_string(), asset_name_part2)
+                    .get_mulle_text_by_name(DRIVE_MOVIE.to_string(), asset_name_part2)
                     .unwrap()
                     .text
                     .as_str(),
@@ -1,27 +1,58 @@
-use std::fs::{self, File};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
+use crate::game_definition::GameDefinition;
+use crate::launch::LaunchSettings;
 use crate::render::scaler::PIXEL_PERFECT_LAYERS;
-use crate::systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper};
+use crate::systems::mulle_asset_helper::{
+    MulleAssetHelp, MulleAssetHelper, MulleAssetState, MulleFilmloop,
+};
+use crate::systems::mulle_filmloop::MulleFilmloopPlayer;
+use crate::systems::mulle_locale::Locale;
 use crate::systems::mulle_point_and_click::{
     deploy_clickables, mulle_clickable_from_name, MulleClickable, MulleClickableSerializable,
 };
+use crate::systems::mulle_text::MulleTextBox;
 use crate::{despawn_screen, GameState};
 use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
 use bevy::utils::hashbrown::hash_map::Values;
 use bevy::utils::hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 
+mod editor;
+pub use editor::RoomEditorPlugin;
+
+// the rooms and their clickables, in the assets folder
+const ROOMS_FILE: &str = "rooms.json";
+
+// the rooms file is checked this often, when it changed the rooms are read again and the current one is rebuilt
+const ROOMS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
 pub struct YardPlugin;
 
 impl Plugin for YardPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostStartup, setup_rooms)
+        app.add_systems(OnEnter(MulleAssetState::Loaded), setup_rooms)
             .add_systems(
                 PreUpdate,
-                despawn_screen::<OnRoomScreen>.run_if(resource_changed::<RoomState>),
+                (
+                    reload_rooms
+                        .run_if(resource_exists::<RoomsFile>)
+                        .run_if(on_timer(ROOMS_POLL_INTERVAL)),
+                    despawn_screen::<OnRoomScreen>.run_if(resource_exists_and_changed::<RoomState>),
+                )
+                    .chain(),
             )
             .add_systems(OnExit(GameState::Room), despawn_screen::<OnRoomScreen>)
-            .add_systems(Update, build_room.run_if(resource_changed::<RoomState>));
+            .add_systems(
+                Update,
+                build_room.run_if(resource_exists_and_changed::<RoomState>),
+            );
     }
 }
 
@@ -29,14 +60,52 @@ impl Plugin for YardPlugin {
 #[derive(Component, Clone)]
 struct OnRoomScreen;
 
+/// What is wrong with the rooms file, shown on top of the game until it is fixed
+#[derive(Component)]
+struct RoomsError;
+
+/// The file the rooms were read from and when it was changed at that time
+#[derive(Resource)]
+struct RoomsFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// Why the last read failed, that is what is shown until the file is fixed
+    error: Option<String>,
+}
+
 #[derive(Resource, Serialize, Deserialize)]
 pub struct RoomState {
     pub current_room: String,
 }
 
+// sorted, so saving the rooms from the editor does not shuffle the file around
 #[derive(Serialize, Deserialize)]
 struct RoomsSerializable {
-    rooms: HashMap<String, MulleRoomSerializable>,
+    rooms: BTreeMap<String, MulleRoomSerializable>,
+}
+
+impl RoomsSerializable {
+    /// The member numbers in the rooms file are those of the original release
+    fn localize(&mut self, locale: &Locale) {
+        for room in self.rooms.values_mut() {
+            room.background_asset_number =
+                locale.member(&room.background_asset_ref, room.background_asset_number);
+            for clickable in room.clickables.values_mut() {
+                // the hover sprite is looked up in the file of the default sprite
+                let dir = &clickable.sprite_default_asset_dir;
+                clickable.sprite_default_asset_number =
+                    locale.member(dir, clickable.sprite_default_asset_number);
+                clickable.sprite_hover_asset_number =
+                    locale.member(dir, clickable.sprite_hover_asset_number);
+            }
+            for filmloop in &mut room.filmloops {
+                filmloop.asset_number = locale.member(&filmloop.asset_dir, filmloop.asset_number);
+            }
+            for text in &mut room.texts {
+                text.asset_number = locale.member(&text.asset_dir, text.asset_number);
+            }
+        }
+    }
 }
 
 #[derive(Resource)]
@@ -66,17 +135,126 @@ impl RoomsConvert for Rooms {
     }
 }
 
-fn setup_rooms(mut commands: Commands, mulle_asset_helper: Res<MulleAssetHelp>) {
+fn setup_rooms(
+    mut commands: Commands,
+    mulle_asset_helper: Res<MulleAssetHelp>,
+    settings: Res<LaunchSettings>,
+    game: Res<GameDefinition>,
+    locale: Res<Locale>,
+    shown_errors: Query<Entity, With<RoomsError>>,
+) {
     commands.insert_resource(RoomState {
-        current_room: "yard".into(),
+        current_room: game.start_room.clone(),
     });
 
-    let json_content = fs::read_to_string("assets/rooms.json").expect("Failed to read rooms file!");
+    let path = settings.assets_dir.join(ROOMS_FILE);
+    let (rooms, error) = match read_rooms(&path, &locale) {
+        Ok(rooms) => (Rooms::convert(rooms, &mulle_asset_helper), None),
+        Err(error) => {
+            // without rooms there is nothing to show, but fixing the file brings them in
+            show_rooms_error(&mut commands, &shown_errors, Some(error.clone()));
+            (
+                Rooms {
+                    rooms: HashMap::default(),
+                },
+                Some(error),
+            )
+        }
+    };
+    commands.insert_resource(rooms);
+    commands.insert_resource(RoomsFile {
+        modified: file_modified(&path),
+        path,
+        error,
+    });
+}
 
-    let rooms: RoomsSerializable =
-        serde_json::from_str(&json_content).expect("Failed to parse json file");
+fn reload_rooms(
+    mut commands: Commands,
+    mut rooms_file: ResMut<RoomsFile>,
+    mut room_state: ResMut<RoomState>,
+    mulle_asset_helper: Res<MulleAssetHelp>,
+    locale: Res<Locale>,
+    shown_errors: Query<Entity, With<RoomsError>>,
+) {
+    let modified = file_modified(&rooms_file.path);
+    if modified == rooms_file.modified {
+        return;
+    }
+    rooms_file.modified = modified;
+    match read_rooms(&rooms_file.path, &locale) {
+        Ok(rooms) => {
+            rooms_file.error = None;
+            show_rooms_error(&mut commands, &shown_errors, None);
+            commands.insert_resource(Rooms::convert(rooms, &mulle_asset_helper));
+            // the current room is despawned and built again like when the room changes
+            room_state.set_changed();
+        }
+        // the rooms that were read before stay until the file is fixed
+        Err(error) => {
+            show_rooms_error(&mut commands, &shown_errors, Some(error.clone()));
+            rooms_file.error = Some(error);
+        }
+    }
+}
+
+fn file_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
 
-    commands.insert_resource(Rooms::convert(rooms, &mulle_asset_helper));
+fn read_rooms(path: &Path, locale: &Locale) -> Result<RoomsSerializable, String> {
+    let mut rooms = parse_rooms(path)?;
+    rooms.localize(locale);
+    Ok(rooms)
+}
+
+/// The rooms like they are in the file, with the member numbers of the original release
+fn parse_rooms(path: &Path) -> Result<RoomsSerializable, String> {
+    let json_content = fs::read_to_string(path)
+        .map_err(|error| format!("can not read {}: {error}", path.display()))?;
+    serde_json::from_str(&json_content)
+        .map_err(|error| format!("can not parse {}: {error}", path.display()))
+}
+
+fn write_rooms(path: &Path, rooms: &RoomsSerializable) -> Result<(), String> {
+    let json_content = serde_json::to_string_pretty(rooms)
+        .map_err(|error| format!("can not write {}: {error}", path.display()))?;
+    fs::write(path, json_content)
+        .map_err(|error| format!("can not write {}: {error}", path.display()))
+}
+
+/// Replaces the error that is shown, `None` takes it away
+fn show_rooms_error(
+    commands: &mut Commands,
+    shown_errors: &Query<Entity, With<RoomsError>>,
+    error: Option<String>,
+) {
+    for entity in shown_errors {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some(error) = error else {
+        return;
+    };
+    eprintln!("{error}");
+    commands.spawn((
+        Text::new(error),
+        TextFont {
+            font_size: 14.,
+            ..default()
+        },
+        TextColor(Color::srgb(1., 0.3, 0.3)),
+        BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.),
+            left: Val::Px(4.),
+            padding: UiRect::all(Val::Px(4.)),
+            ..default()
+        },
+        RoomsError,
+    ));
 }
 
 fn build_room(
@@ -84,25 +262,53 @@ fn build_room(
     mulle_asset_helper: Res<MulleAssetHelp>,
     rooms: Res<Rooms>,
     roomstate: Res<RoomState>,
+    rooms_file: Res<RoomsFile>,
+    shown_errors: Query<Entity, With<RoomsError>>,
 ) {
     print!("Switched room {}", roomstate.current_room);
-    let room = rooms
-        .rooms
-        .get(&roomstate.current_room)
-        .unwrap_or_else(|| panic!("Failed to get requested room: {}", roomstate.current_room));
+    let Some(room) = rooms.rooms.get(&roomstate.current_room) else {
+        // a file that does not read has no rooms at all, its own error says more
+        if rooms_file.error.is_some() {
+            return;
+        }
+        let error = format!("{ROOMS_FILE} has no room {}", roomstate.current_room);
+        show_rooms_error(&mut commands, &shown_errors, Some(error));
+        return;
+    };
     // Render background
-    commands.spawn((
-        mulle_asset_helper
-            .get_image_by_asset_number(
-                room.background_asset_ref.to_string(),
-                room.background_asset_number,
-            )
-            .unwrap()
-            .clone(),
-        Transform::from_xyz(0., 0., 0.),
-        OnRoomScreen,
-        PIXEL_PERFECT_LAYERS,
-    ));
+    match mulle_asset_helper.get_image_by_asset_number(
+        room.background_asset_ref.to_string(),
+        room.background_asset_number,
+    ) {
+        Some(background) => {
+            commands.spawn((
+                background.clone(),
+                Transform::from_xyz(0., 0., 0.),
+                OnRoomScreen,
+                PIXEL_PERFECT_LAYERS,
+            ));
+        }
+        None => eprintln!(
+            "room background {} {} not found",
+            room.background_asset_ref, room.background_asset_number
+        ),
+    }
+
+    for (filmloop, position) in &room.filmloops {
+        commands.spawn((
+            MulleFilmloopPlayer::new(filmloop.clone()),
+            Transform::from_translation(position.extend(1.)),
+            OnRoomScreen,
+        ));
+    }
+
+    for (dir, member, position) in &room.texts {
+        commands.spawn((
+            MulleTextBox::new(dir.clone(), *member),
+            Transform::from_translation(position.extend(2.)),
+            OnRoomScreen,
+        ));
+    }
 
     deploy_clickables::<OnRoomScreen, Values<String, MulleClickable>>(
         commands,
@@ -115,13 +321,37 @@ fn build_room(
 struct MulleRoomSerializable {
     background_asset_ref: String,
     background_asset_number: u32,
-    clickables: HashMap<String, MulleClickableSerializable>,
+    clickables: BTreeMap<String, MulleClickableSerializable>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    filmloops: Vec<MulleFilmloopSerializable>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    texts: Vec<MulleTextSerializable>,
+}
+
+/// A filmloop member that plays in the room, x and y are where the middle of the filmloop goes
+#[derive(Deserialize, Serialize)]
+struct MulleFilmloopSerializable {
+    asset_dir: String,
+    asset_number: u32,
+    x: f32,
+    y: f32,
+}
+
+/// A text member shown in the room, x and y are where the top left corner of the text goes
+#[derive(Deserialize, Serialize)]
+struct MulleTextSerializable {
+    asset_dir: String,
+    asset_number: u32,
+    x: f32,
+    y: f32,
 }
 
 struct MulleRoom {
     background_asset_ref: String,
     background_asset_number: u32,
     clickables: HashMap<String, MulleClickable>,
+    filmloops: Vec<(Handle<MulleFilmloop>, Vec2)>,
+    texts: Vec<(String, u32, Vec2)>,
 }
 
 trait MulleRoomConvert {
@@ -139,28 +369,70 @@ impl MulleRoomConvert for MulleRoom {
         let clickables_vec: Vec<(String, MulleClickable)> = value
             .clickables
             .iter()
-            .map(|(name, clickable)| {
-                (
-                    name.clone(),
-                    mulle_clickable_from_name(
-                        clickable.click.clone(),
-                        &clickable.sprite_default_asset_dir,
+            .filter_map(|(name, clickable)| {
+                let mulle_clickable = mulle_clickable_from_name(
+                    clickable.click.clone(),
+                    &clickable.sprite_default_asset_dir,
+                    clickable.sprite_default_asset_number,
+                    &clickable.sprite_hover_asset_dir,
+                    clickable.sprite_hover_asset_number,
+                    mulle_asset_helper,
+                );
+                if mulle_clickable.is_none() {
+                    eprintln!(
+                        "room clickable {name} has no sprite {} {} or {}",
+                        clickable.sprite_default_asset_dir,
                         clickable.sprite_default_asset_number,
-                        &clickable.sprite_hover_asset_dir,
-                        clickable.sprite_hover_asset_number,
-                        mulle_asset_helper,
-                    ),
-                )
+                        clickable.sprite_hover_asset_number
+                    );
+                }
+                Some((name.clone(), mulle_clickable?))
             })
             .collect();
         let mut clickables: HashMap<String, MulleClickable> = HashMap::default();
         for (k, v) in clickables_vec {
             clickables.insert(k, v);
         }
+        let filmloops = value
+            .filmloops
+            .iter()
+            .filter_map(|filmloop| {
+                let member = mulle_asset_helper.get_mulle_filmloop_by_asset_number(
+                    filmloop.asset_dir.clone(),
+                    filmloop.asset_number,
+                );
+                if member.is_none() {
+                    eprintln!(
+                        "room filmloop {} {} not found",
+                        filmloop.asset_dir, filmloop.asset_number
+                    );
+                }
+                Some((member?.filmloop.clone(), Vec2::new(filmloop.x, filmloop.y)))
+            })
+            .collect();
+        let texts = value
+            .texts
+            .into_iter()
+            .filter(|text| {
+                let found = mulle_asset_helper
+                    .get_mulle_text_by_asset_number(text.asset_dir.clone(), text.asset_number)
+                    .is_some();
+                if !found {
+                    eprintln!(
+                        "room text {} {} not found",
+                        text.asset_dir, text.asset_number
+                    );
+                }
+                found
+            })
+            .map(|text| (text.asset_dir, text.asset_number, Vec2::new(text.x, text.y)))
+            .collect();
         Self {
             background_asset_number: value.background_asset_number,
             background_asset_ref: value.background_asset_ref,
             clickables,
+            filmloops,
+            texts,
         }
     }
 }
@@ -0,0 +1,631 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use super::{parse_rooms, write_rooms, RoomState, RoomsFile, RoomsSerializable, ROOMS_FILE};
+use crate::game_definition::GameDefinition;
+use crate::systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper, Named};
+use crate::systems::mulle_locale::Locale;
+use crate::systems::mulle_point_and_click::{
+    ClickAction, ClicksPaused, MulleClickableSerializable,
+};
+use crate::GameState;
+
+// A panel next to the room to put rooms.json together without guessing member numbers, F2 opens and closes it
+// when the game runs with --editor
+// Page through the members of a file, a click on one picks the default sprite and the next one the hover sprite,
+// the actions are picked from lists and "place" adds the clickable to the room that is shown
+// Every change is written to rooms.json right away and comes back in through the reload like any other edit
+// The members are picked from the files that are loaded, but saved as the numbers of the original release that
+// rooms.json holds, see mulle_locale
+
+const TOGGLE_KEY: KeyCode = KeyCode::F2;
+const PANEL_WIDTH: f32 = 300.;
+const THUMBNAIL_SIZE: f32 = 52.;
+const THUMBNAILS_PER_PAGE: usize = 20;
+const FONT_SIZE: f32 = 12.;
+
+// the states a click can send the game to
+const GAME_STATES: [GameState; 7] = [
+    GameState::DaHood,
+    GameState::GarageWithoutCar,
+    GameState::GarageWithCar,
+    GameState::YardWithoutCar,
+    GameState::YardWithCar,
+    GameState::TrashHeap,
+    GameState::Room,
+];
+
+pub struct RoomEditorPlugin;
+
+impl Plugin for RoomEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle_editor,
+                press_buttons.run_if(resource_exists::<RoomEditor>),
+                draw_editor.run_if(resource_exists_and_changed::<RoomEditor>.or(
+                    resource_exists::<RoomEditor>.and(resource_exists_and_changed::<RoomState>),
+                )),
+            )
+                .chain()
+                .run_if(in_state(GameState::Room))
+                .run_if(resource_exists::<RoomsFile>),
+        )
+        .add_systems(OnExit(GameState::Room), close_editor);
+    }
+}
+
+/// What is picked in the editor, the rooms themselves are always read from the file
+#[derive(Resource, Default)]
+struct RoomEditor {
+    /// Index in the files of the game
+    file: usize,
+    page: usize,
+    picking: Picking,
+    sprite_default: Option<u32>,
+    sprite_hover: Option<u32>,
+    action: ActionKind,
+    /// Index in the targets of `action`
+    target: usize,
+    actions: Vec<ClickAction>,
+    status: String,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Picking {
+    #[default]
+    Default,
+    Hover,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum ActionKind {
+    #[default]
+    RoomstateTransition,
+    GamestateTransition,
+    RunLingo,
+}
+
+impl ActionKind {
+    const fn next(self) -> Self {
+        match self {
+            Self::RoomstateTransition => Self::GamestateTransition,
+            Self::GamestateTransition => Self::RunLingo,
+            Self::RunLingo => Self::RoomstateTransition,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::RoomstateTransition => "go to room",
+            Self::GamestateTransition => "go to game state",
+            Self::RunLingo => "run lingo handler",
+        }
+    }
+}
+
+#[derive(Component)]
+struct EditorPanel;
+
+#[derive(Component, Clone)]
+enum EditorButton {
+    Room(isize),
+    File(isize),
+    Page(isize),
+    Member(u32),
+    Picking,
+    Action,
+    Target(isize),
+    AddAction,
+    ClearActions,
+    Place,
+    Remove(String),
+}
+
+fn toggle_editor(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    editor: Option<Res<RoomEditor>>,
+    panels: Query<Entity, With<EditorPanel>>,
+) {
+    if !keyboard_input.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+    if editor.is_some() {
+        close(&mut commands, &panels);
+    } else {
+        commands.init_resource::<RoomEditor>();
+        // clicking in the panel should not click the room behind it
+        commands.insert_resource(ClicksPaused);
+    }
+}
+
+fn close_editor(mut commands: Commands, panels: Query<Entity, With<EditorPanel>>) {
+    close(&mut commands, &panels);
+}
+
+fn close(commands: &mut Commands, panels: &Query<Entity, With<EditorPanel>>) {
+    for entity in panels {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<RoomEditor>();
+    commands.remove_resource::<ClicksPaused>();
+}
+
+/// Moves `index` by `delta` around a list of `len`
+const fn step(index: usize, delta: isize, len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    (index as isize + delta).rem_euclid(len as isize) as usize
+}
+
+fn room_names(rooms: &RoomsSerializable) -> Vec<String> {
+    rooms.rooms.keys().cloned().collect()
+}
+
+/// What the action that is being put together can point at
+fn action_targets(
+    kind: ActionKind,
+    rooms: &[String],
+    dir: &str,
+    mulle_asset_helper: &MulleAssetHelp,
+) -> Vec<String> {
+    match kind {
+        ActionKind::RoomstateTransition => rooms.to_vec(),
+        ActionKind::GamestateTransition => GAME_STATES
+            .iter()
+            .map(|state| format!("{state:?}"))
+            .collect(),
+        ActionKind::RunLingo => {
+            let mut handlers: Vec<String> = mulle_asset_helper
+                .get_lingo_context(dir.to_owned())
+                .into_iter()
+                .flat_map(|lingo| lingo.scripts.values())
+                .flat_map(|script| script.handlers.iter().map(|handler| handler.name.clone()))
+                .collect();
+            handlers.sort_unstable();
+            handlers.dedup();
+            handlers
+        }
+    }
+}
+
+fn current_action_targets(
+    editor: &RoomEditor,
+    path: &Path,
+    dir: &str,
+    mulle_asset_helper: &MulleAssetHelp,
+) -> Vec<String> {
+    let rooms = parse_rooms(path)
+        .map(|rooms| room_names(&rooms))
+        .unwrap_or_default();
+    action_targets(editor.action, &rooms, dir, mulle_asset_helper)
+}
+
+fn make_action(
+    kind: ActionKind,
+    target: usize,
+    targets: &[String],
+    dir: &str,
+) -> Option<ClickAction> {
+    let name = targets.get(target)?.clone();
+    Some(match kind {
+        ActionKind::RoomstateTransition => ClickAction::RoomstateTransition { goal_state: name },
+        ActionKind::GamestateTransition => ClickAction::GamestateTransition {
+            goal_state: *GAME_STATES.get(target)?,
+        },
+        ActionKind::RunLingo => ClickAction::RunLingo {
+            asset_dir: dir.to_owned(),
+            handler: name,
+        },
+    })
+}
+
+fn press_buttons(
+    buttons: Query<(&Interaction, &EditorButton), Changed<Interaction>>,
+    mut editor: ResMut<RoomEditor>,
+    mut room_state: ResMut<RoomState>,
+    rooms_file: Res<RoomsFile>,
+    game: Res<GameDefinition>,
+    mulle_asset_helper: Res<MulleAssetHelp>,
+    locale: Res<Locale>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let dir = game.files.get(editor.file).map_or("", String::as_str);
+        match button {
+            EditorButton::Room(delta) => {
+                let rooms = parse_rooms(&rooms_file.path)
+                    .map(|rooms| room_names(&rooms))
+                    .unwrap_or_default();
+                let index = rooms
+                    .iter()
+                    .position(|room| *room == room_state.current_room)
+                    .unwrap_or_default();
+                if let Some(room) = rooms.get(step(index, *delta, rooms.len())) {
+                    room_state.current_room = room.clone();
+                }
+            }
+            EditorButton::File(delta) => {
+                // the picked members belong to the other file
+                *editor = RoomEditor {
+                    file: step(editor.file, *delta, game.files.len()),
+                    actions: std::mem::take(&mut editor.actions),
+                    ..default()
+                };
+            }
+            EditorButton::Page(delta) => {
+                let pages = mulle_asset_helper
+                    .image_members(dir)
+                    .len()
+                    .div_ceil(THUMBNAILS_PER_PAGE);
+                editor.page = step(editor.page, *delta, pages);
+            }
+            EditorButton::Member(number) => match editor.picking {
+                Picking::Default => {
+                    editor.sprite_default = Some(*number);
+                    editor.picking = Picking::Hover;
+                }
+                Picking::Hover => editor.sprite_hover = Some(*number),
+            },
+            EditorButton::Picking => {
+                editor.picking = match editor.picking {
+                    Picking::Default => Picking::Hover,
+                    Picking::Hover => Picking::Default,
+                };
+            }
+            EditorButton::Action => {
+                editor.action = editor.action.next();
+                editor.target = 0;
+            }
+            EditorButton::Target(delta) => {
+                let targets =
+                    current_action_targets(&editor, &rooms_file.path, dir, &mulle_asset_helper);
+                editor.target = step(editor.target, *delta, targets.len());
+            }
+            EditorButton::AddAction => {
+                let targets =
+                    current_action_targets(&editor, &rooms_file.path, dir, &mulle_asset_helper);
+                match make_action(editor.action, editor.target, &targets, dir) {
+                    Some(action) => editor.actions.push(action),
+                    None => editor.status = format!("there is nothing to {}", editor.action.name()),
+                }
+            }
+            EditorButton::ClearActions => editor.actions.clear(),
+            EditorButton::Place => {
+                editor.status = match place_clickable(
+                    &editor,
+                    dir,
+                    &room_state.current_room,
+                    &rooms_file.path,
+                    &mulle_asset_helper,
+                    &locale,
+                ) {
+                    Ok(name) => {
+                        editor.sprite_default = None;
+                        editor.sprite_hover = None;
+                        editor.picking = Picking::Default;
+                        editor.actions.clear();
+                        format!("placed {name}")
+                    }
+                    Err(error) => error,
+                };
+            }
+            EditorButton::Remove(name) => {
+                editor.status =
+                    match remove_clickable(name, &room_state.current_room, &rooms_file.path) {
+                        Ok(()) => format!("removed {name}"),
+                        Err(error) => error,
+                    };
+            }
+        }
+    }
+}
+
+/// Adds the picked sprites and actions to `room` in the file, named after the default sprite
+fn place_clickable(
+    editor: &RoomEditor,
+    dir: &str,
+    room: &str,
+    path: &Path,
+    mulle_asset_helper: &MulleAssetHelp,
+    locale: &Locale,
+) -> Result<String, String> {
+    let sprite_default = editor
+        .sprite_default
+        .ok_or("pick the default sprite first")?;
+    let original = |number| {
+        locale.original_member(dir, number).ok_or_else(|| {
+            format!(
+                "no member of the original release is {dir} member {number} in the {:?} release, rooms.json can not point at it",
+                locale.language
+            )
+        })
+    };
+    let original_default = original(sprite_default)?;
+    // without a hover sprite it looks the same under the mouse
+    let original_hover = original(editor.sprite_hover.unwrap_or(sprite_default))?;
+    let mut rooms = parse_rooms(path)?;
+    let clickables = &mut rooms
+        .rooms
+        .get_mut(room)
+        .ok_or_else(|| format!("{ROOMS_FILE} has no room {room}"))?
+        .clickables;
+
+    let member_name = mulle_asset_helper
+        .get_mulle_file_by_asset_number(dir.to_owned(), sprite_default)
+        .map(Named::name)
+        .unwrap_or_default();
+    let base: String = member_name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let base = if base.is_empty() {
+        "clickable".to_owned()
+    } else {
+        base
+    };
+    let name = (1..)
+        .map(|count| {
+            if count == 1 {
+                base.clone()
+            } else {
+                format!("{base}_{count}")
+            }
+        })
+        .find(|name| !clickables.contains_key(name))
+        .unwrap_or(base);
+
+    clickables.insert(
+        name.clone(),
+        MulleClickableSerializable {
+            sprite_default_asset_dir: dir.to_owned(),
+            sprite_default_asset_number: original_default,
+            sprite_hover_asset_dir: dir.to_owned(),
+            sprite_hover_asset_number: original_hover,
+            click: editor.actions.clone(),
+        },
+    );
+    write_rooms(path, &rooms)?;
+    Ok(name)
+}
+
+fn remove_clickable(name: &str, room: &str, path: &Path) -> Result<(), String> {
+    let mut rooms = parse_rooms(path)?;
+    rooms
+        .rooms
+        .get_mut(room)
+        .and_then(|room| room.clickables.remove(name))
+        .ok_or_else(|| format!("{room} has no clickable {name}"))?;
+    write_rooms(path, &rooms)
+}
+
+fn text(content: impl Into<String>) -> (Text, TextFont) {
+    (
+        Text::new(content),
+        TextFont {
+            font_size: FONT_SIZE,
+            ..default()
+        },
+    )
+}
+
+fn button(parent: &mut ChildBuilder, label: impl Into<String>, action: EditorButton) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.25, 0.25, 0.3)),
+            action,
+        ))
+        .with_children(|button| {
+            button.spawn(text(label));
+        });
+}
+
+/// A label with buttons to go back and forth through a list
+fn stepper(parent: &mut ChildBuilder, label: impl Into<String>, action: fn(isize) -> EditorButton) {
+    parent.spawn(row()).with_children(|row| {
+        button(row, "<", action(-1));
+        button(row, ">", action(1));
+        row.spawn(text(label));
+    });
+}
+
+fn row() -> Node {
+    Node {
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(4.),
+        ..default()
+    }
+}
+
+fn draw_editor(
+    mut commands: Commands,
+    panels: Query<Entity, With<EditorPanel>>,
+    editor: Res<RoomEditor>,
+    room_state: Res<RoomState>,
+    rooms_file: Res<RoomsFile>,
+    game: Res<GameDefinition>,
+    mulle_asset_helper: Res<MulleAssetHelp>,
+) {
+    for entity in &panels {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let dir = game.files.get(editor.file).map_or("", String::as_str);
+    let members = mulle_asset_helper.image_members(dir);
+    let pages = members.len().div_ceil(THUMBNAILS_PER_PAGE).max(1);
+    let (rooms, clickables, error) = match parse_rooms(&rooms_file.path) {
+        Ok(rooms) => {
+            let clickables: Vec<String> = rooms
+                .rooms
+                .get(&room_state.current_room)
+                .map(|room| room.clickables.keys().cloned().collect())
+                .unwrap_or_default();
+            (room_names(&rooms), clickables, None)
+        }
+        Err(error) => (Vec::new(), Vec::new(), Some(error)),
+    };
+    let targets = action_targets(editor.action, &rooms, dir, &mulle_asset_helper);
+    let member_label = |number: Option<u32>| {
+        number.map_or_else(
+            || "-".to_owned(),
+            |number| {
+                let name = mulle_asset_helper
+                    .get_mulle_file_by_asset_number(dir.to_owned(), number)
+                    .map(Named::name)
+                    .unwrap_or_default();
+                format!("{number} {name}")
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.),
+                right: Val::Px(0.),
+                width: Val::Px(PANEL_WIDTH),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.),
+                padding: UiRect::all(Val::Px(6.)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.85)),
+            EditorPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn(text("room editor, F2 closes it"));
+            stepper(
+                panel,
+                format!("room {}", room_state.current_room),
+                EditorButton::Room,
+            );
+            stepper(panel, format!("file {dir}"), EditorButton::File);
+            stepper(
+                panel,
+                format!("page {}/{pages}", editor.page + 1),
+                EditorButton::Page,
+            );
+
+            panel
+                .spawn(Node {
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: Val::Px(2.),
+                    row_gap: Val::Px(2.),
+                    ..default()
+                })
+                .with_children(|grid| {
+                    for (number, _) in members
+                        .iter()
+                        .skip(editor.page * THUMBNAILS_PER_PAGE)
+                        .take(THUMBNAILS_PER_PAGE)
+                    {
+                        let border = if editor.sprite_default == Some(*number) {
+                            Color::srgb(0.2, 0.9, 0.2)
+                        } else if editor.sprite_hover == Some(*number) {
+                            Color::srgb(0.3, 0.5, 1.)
+                        } else {
+                            Color::srgb(0.3, 0.3, 0.3)
+                        };
+                        grid.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(THUMBNAIL_SIZE),
+                                height: Val::Px(THUMBNAIL_SIZE),
+                                border: UiRect::all(Val::Px(2.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BorderColor(border),
+                            BackgroundColor(Color::srgb(0.5, 0.5, 0.5)),
+                            EditorButton::Member(*number),
+                        ))
+                        .with_children(|thumbnail| {
+                            if let Some(sprite) = mulle_asset_helper
+                                .get_image_by_asset_number(dir.to_owned(), *number)
+                            {
+                                thumbnail.spawn((
+                                    ImageNode::new(sprite.image.clone()),
+                                    Node {
+                                        max_width: Val::Percent(100.),
+                                        max_height: Val::Percent(100.),
+                                        ..default()
+                                    },
+                                ));
+                            }
+                            thumbnail.spawn((
+                                text(number.to_string()),
+                                Node {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Px(1.),
+                                    bottom: Val::Px(0.),
+                                    ..default()
+                                },
+                            ));
+                        });
+                    }
+                });
+
+            button(
+                panel,
+                match editor.picking {
+                    Picking::Default => "a click picks the default sprite",
+                    Picking::Hover => "a click picks the hover sprite",
+                },
+                EditorButton::Picking,
+            );
+            panel.spawn(text(format!(
+                "default {}\nhover {}",
+                member_label(editor.sprite_default),
+                member_label(editor.sprite_hover)
+            )));
+
+            button(panel, editor.action.name(), EditorButton::Action);
+            stepper(
+                panel,
+                targets
+                    .get(editor.target)
+                    .cloned()
+                    .unwrap_or_else(|| "-".to_owned()),
+                EditorButton::Target,
+            );
+            panel.spawn(row()).with_children(|row| {
+                button(row, "add action", EditorButton::AddAction);
+                button(row, "clear actions", EditorButton::ClearActions);
+            });
+            for action in &editor.actions {
+                panel.spawn(text(format!("  {action:?}")));
+            }
+            button(panel, "place in room", EditorButton::Place);
+
+            panel.spawn(text("in this room"));
+            for name in clickables {
+                panel.spawn(row()).with_children(|row| {
+                    button(row, "x", EditorButton::Remove(name.clone()));
+                    row.spawn(text(name));
+                });
+            }
+
+            let status = error.unwrap_or_else(|| editor.status.clone());
+            if !status.is_empty() {
+                panel.spawn((text(status), TextColor(Color::srgb(1., 0.8, 0.3))));
+            }
+        });
+}
@@ -1,3 +1,7 @@
 pub mod mulle_asset_helper;
 pub mod mulle_car;
+pub mod mulle_filmloop;
+pub mod mulle_lingo;
+pub mod mulle_locale;
 pub mod mulle_point_and_click;
+pub mod mulle_text;
@@ -0,0 +1,190 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    parsers::{database_language::MapData, director::ink::Transparency},
+    systems::mulle_car::PartDB,
+};
+
+// Decoded members are kept in assets/cache/<game>/<file>/ so the next launch does not have to decode them again
+// Every file gets an index with the size and hash of the file it was made from and its parsed databases,
+// next to it every decoded member is a file named after the slot of its chunk
+// A cache that was made from another file or by another CACHE_VERSION is thrown away when the file is loaded,
+// and so is every cache when openMulle is started with --rebuild-cache
+
+const INDEX_FILE: &str = "index.bin";
+
+// bump this when anything that ends up in the cache is decoded differently
+const CACHE_VERSION: u32 = 4;
+
+#[derive(Serialize, Deserialize)]
+struct CacheIndex {
+    version: u32,
+    source_size: u64,
+    source_hash: u64,
+    databases: CachedDatabases,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CachedDatabases {
+    pub map_db: HashMap<i32, MapData>,
+    pub part_db: HashMap<i32, PartDB>,
+}
+
+/// Decoded pixels, they only fit the bitmap as long as its transparency is not overridden differently
+#[derive(Serialize, Deserialize)]
+pub struct CachedImage {
+    pub transparency: Transparency,
+    pub rgba: Vec<u8>,
+}
+
+/// The cache of one director file
+pub struct DecodeCache {
+    /// `None` when the cache directory can not be used, nothing is read or written then
+    path: Option<PathBuf>,
+    source_size: u64,
+    source_hash: u64,
+    databases: Option<CachedDatabases>,
+}
+
+impl DecodeCache {
+    /// Opens the cache of `dir` in `game` under `cache_dir`, it is emptied when it does not belong to `source`
+    /// or when it is to be rebuilt
+    pub fn open(cache_dir: &Path, game: &str, dir: &str, source: &[u8], rebuild: bool) -> Self {
+        let path = cache_dir.join(game).join(dir);
+        let source_size = source.len() as u64;
+        let source_hash = hash(source);
+
+        let index = if rebuild { None } else { read_index(&path) };
+        if let Some(index) = index.filter(|index| {
+            index.version == CACHE_VERSION
+                && index.source_size == source_size
+                && index.source_hash == source_hash
+        }) {
+            return Self {
+                path: Some(path),
+                source_size,
+                source_hash,
+                databases: Some(index.databases),
+            };
+        }
+
+        // whatever is in there was decoded from something else
+        let emptied = match fs::remove_dir_all(&path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => fs::create_dir_all(&path),
+        };
+        let path = match emptied {
+            Ok(()) => Some(path),
+            Err(error) => {
+                eprintln!("not caching {dir}: {error}");
+                None
+            }
+        };
+        Self {
+            path,
+            source_size,
+            source_hash,
+            databases: None,
+        }
+    }
+
+    /// The databases from the last time the file was loaded, `None` when they have to be parsed again
+    pub const fn databases(&self) -> Option<&CachedDatabases> {
+        self.databases.as_ref()
+    }
+
+    /// Writes the index, until then the cache is thrown away on the next launch
+    pub fn store_databases(&self, databases: CachedDatabases) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        write(
+            &path.join(INDEX_FILE),
+            &CacheIndex {
+                version: CACHE_VERSION,
+                source_size: self.source_size,
+                source_hash: self.source_hash,
+                databases,
+            },
+        );
+    }
+
+    /// The member decoded from the chunk in `slot`, if it was decoded before
+    pub fn read<T: DeserializeOwned>(&self, slot: u32) -> Option<T> {
+        read(&self.path.as_ref()?.join(format!("{slot}.bin")))
+    }
+
+    pub fn write<T: Serialize>(&self, slot: u32, member: &T) {
+        if let Some(path) = &self.path {
+            write(&path.join(format!("{slot}.bin")), member);
+        }
+    }
+}
+
+fn read_index(path: &Path) -> Option<CacheIndex> {
+    read(&path.join(INDEX_FILE))
+}
+
+fn read<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let bytes = fs::read(path).ok()?; // not cached yet
+    bincode::deserialize(&bytes)
+        .inspect_err(|error| eprintln!("ignoring {}: {error}", path.display()))
+        .ok()
+}
+
+fn write<T: Serialize>(path: &Path, value: &T) {
+    let written = bincode::serialize(value)
+        .map_err(io::Error::other)
+        .and_then(|bytes| fs::write(path, bytes));
+    if let Err(error) = written {
+        eprintln!("could not write {}: {error}", path.display());
+    }
+}
+
+// FNV-1a, the hashers of std are not promised to give the same hash on another release
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kept_until_rebuilt_or_changed() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("openmulle-cache-{}", std::process::id()));
+        let open = |source: &[u8], rebuild| {
+            DecodeCache::open(&cache_dir, "cars", "02.dxr", source, rebuild)
+        };
+
+        let cache = open(b"movie", false);
+        assert!(cache.databases().is_none());
+        cache.write(66, &"decoded".to_owned());
+        cache.store_databases(CachedDatabases::default());
+
+        let cache = open(b"movie", false);
+        assert!(cache.databases().is_some());
+        assert_eq!(cache.read::<String>(66).as_deref(), Some("decoded"));
+
+        let rebuilt = open(b"movie", true);
+        assert!(rebuilt.databases().is_none());
+        assert_eq!(rebuilt.read::<String>(66), None);
+
+        rebuilt.write(66, &"decoded".to_owned());
+        rebuilt.store_databases(CachedDatabases::default());
+        let changed = open(b"other movie", false);
+        assert!(changed.databases().is_none());
+        assert_eq!(changed.read::<String>(66), None);
+
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+}
@@ -0,0 +1,567 @@
+use std::{collections::HashMap, io::Cursor, path::PathBuf, sync::Arc};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    decode_cache::{CachedDatabases, DecodeCache},
+    file_stem,
+    lazy_member::{ButtonSource, ImageSource, LazyButton, LazyImage, LazyText, TextSource},
+    read_filmloop_rect, read_palette_member, read_sound_member, resolve_casts, MulleAudio,
+    MulleFile, MulleFilmloop, MulleFilmloopMember, MulleLibrary, MullePalette, MulleSound,
+};
+use crate::{
+    game_definition::TransparencyOverrides,
+    parsers::{
+        database_language::{try_get_mulledb, MapData, MulleDB},
+        director::{
+            ink::{Ink, Transparency},
+            score::{self, MacromediaMemberRef, MacromediaSprite},
+            shape, text, AssetParseError, DirectorFile, MacromediaCastBitmapMetadata,
+            MacromediaCastMember,
+        },
+    },
+    systems::mulle_car::PartDB,
+};
+
+// Director files are loaded by the asset server in the background, a file becomes one `DirectorMovie`
+// Its sounds and filmloops are labelled sub-assets named after their member number, so "02.dxr#66"
+// is the sound of member 66 of 02.dxr. Images and texts are only decoded when they are first used, see lazy_member
+
+/// A decoded director file, `MulleAssetHelp` picks it up once it is loaded
+#[derive(Asset, TypePath)]
+pub struct DirectorMovie {
+    library: MulleLibrary,
+    map_db: HashMap<i32, MapData>,
+    part_db: HashMap<i32, PartDB>,
+}
+
+impl DirectorMovie {
+    pub(super) fn add_to(&self, dir: &str, all_metadata: &mut super::MulleAssetHelp) {
+        all_metadata
+            .metadatafiles
+            .insert(dir.to_owned(), self.library.clone());
+        all_metadata.map_db.extend(self.map_db.clone());
+        all_metadata.part_db.extend(self.part_db.clone());
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct DirectorMovieSettings {
+    /// The name the game knows the file by, it can differ from the file when the disc has .dcr files or other casing
+    pub dir: String,
+}
+
+pub struct DirectorMovieLoader {
+    /// The game that is running, other games have files with the same names
+    pub(super) game: String,
+    pub(super) cache_dir: PathBuf,
+    /// Throws away what was cached and decodes everything again
+    pub(super) rebuild_cache: bool,
+    /// The files of the game, the casts a movie links are looked up in here
+    pub(super) files: Vec<String>,
+    pub(super) transparency_overrides: TransparencyOverrides,
+}
+
+impl AssetLoader for DirectorMovieLoader {
+    type Asset = DirectorMovie;
+    type Settings = DirectorMovieSettings;
+    type Error = AssetParseError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &DirectorMovieSettings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<DirectorMovie, AssetParseError> {
+        let dir = if settings.dir.is_empty() {
+            load_context
+                .path()
+                .file_name()
+                .map_or_else(String::new, |name| name.to_string_lossy().to_lowercase())
+        } else {
+            settings.dir.clone()
+        };
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(AssetParseError::truncated(&dir, 0))?;
+        let cache = Arc::new(DecodeCache::open(
+            &self.cache_dir,
+            &self.game,
+            &dir,
+            &bytes,
+            self.rebuild_cache,
+        ));
+        let mut director_file = DirectorFile::open(&dir, Cursor::new(bytes))?;
+        Ok(self.decode(&dir, &mut director_file, &cache, load_context))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dxr", "cxt", "dcr", "cct"]
+    }
+}
+
+type MovieFile = DirectorFile<Cursor<Vec<u8>>>;
+
+impl DirectorMovieLoader {
+    // at this point this is getting kinda silly, just a few steps removed from a complete macromedia director converter
+    fn decode(
+        &self,
+        dir: &str,
+        director_file: &mut MovieFile,
+        cache: &Arc<DecodeCache>,
+        load_context: &mut LoadContext,
+    ) -> DirectorMovie {
+        let library = self.read_library(dir, director_file);
+        let mut movie = MovieDecoder {
+            dir,
+            shared_dir: dir.into(),
+            cache,
+            transparency_overrides: &self.transparency_overrides,
+            own_casts: own_casts(dir, &library),
+            library,
+            sprite_inks: HashMap::new(),
+            bitmap_meta: HashMap::new(),
+            // the databases only have to be parsed when they are not cached
+            parse_databases: cache.databases().is_none(),
+            databases: cache.databases().cloned().unwrap_or_default(),
+        };
+        if let Some(score) = &movie.library.score {
+            collect_inks(
+                &mut movie.sprite_inks,
+                score.frames.iter().flat_map(|frame| &frame.sprites),
+                &movie.own_casts,
+            );
+        }
+
+        let members = std::mem::take(&mut director_file.members);
+        // palettes are needed before the bitmaps that use them, and so are the inks of the filmloops
+        for member in &members {
+            match member.cast_type {
+                1 => movie.read_bitmap_metadata(member),
+                2 => movie.read_filmloop(director_file, member, load_context),
+                4 => movie.read_palette(director_file, member),
+                _ => {} // the other members are read per chunk below
+            }
+        }
+        for member in &members {
+            match member.cast_type {
+                // a sound can be split over several chunks, so it is read per member
+                6 => movie.read_sound(director_file, member, load_context),
+                // shapes have no chunks, they are drawn from their specific data
+                8 => movie.read_shape(member),
+                _ => {
+                    for (linked_type, linked_item) in director_file.linked_chunks(member) {
+                        movie.read_chunk(director_file, member, &linked_type, linked_item);
+                    }
+                }
+            }
+        }
+        movie.finish()
+    }
+
+    /// Everything of the file that is not a cast member
+    fn read_library(&self, dir: &str, director_file: &mut MovieFile) -> MulleLibrary {
+        let cast_list = director_file.cast_list().unwrap_or_else(|error| {
+            eprintln!("skipping cast list: {error}");
+            Vec::new()
+        });
+        let name = cast_list
+            .iter()
+            .find(|cast| cast.file_path.is_empty())
+            .map_or_else(|| file_stem(dir).to_owned(), |cast| cast.name.clone());
+        let mut library = MulleLibrary {
+            casts: resolve_casts(dir, &name, &cast_list, &self.files),
+            name,
+            config: director_file.config.clone(),
+            fonts: director_file.font_map().unwrap_or_else(|error| {
+                eprintln!("skipping font map: {error}");
+                HashMap::new()
+            }),
+            files: HashMap::new(),
+            score: None,
+            lingo: None,
+        };
+        library.lingo = director_file.lingo().unwrap_or_else(|error| {
+            eprintln!("skipping scripts: {error}");
+            None
+        });
+        for error in &director_file.member_errors {
+            eprintln!("skipping cast member: {error}");
+        }
+        library.score = director_file.score().unwrap_or_else(|error| {
+            eprintln!("skipping score: {error}");
+            None
+        });
+        library
+    }
+}
+
+/// What is built up while going through the members of one file
+struct MovieDecoder<'a> {
+    dir: &'a str,
+    shared_dir: Arc<str>,
+    cache: &'a Arc<DecodeCache>,
+    transparency_overrides: &'a TransparencyOverrides,
+    library: MulleLibrary,
+    /// The cast numbers the sprites of this file use for its own members
+    own_casts: Vec<i16>,
+    sprite_inks: HashMap<MacromediaMemberRef, Ink>,
+    bitmap_meta: HashMap<u32, MacromediaCastBitmapMetadata>,
+    parse_databases: bool,
+    databases: CachedDatabases,
+}
+
+impl MovieDecoder<'_> {
+    fn finish(self) -> DirectorMovie {
+        if self.parse_databases {
+            self.cache.store_databases(self.databases.clone());
+        }
+        DirectorMovie {
+            library: self.library,
+            map_db: self.databases.map_db,
+            part_db: self.databases.part_db,
+        }
+    }
+
+    fn read_bitmap_metadata(&mut self, member: &MacromediaCastMember) {
+        match MacromediaCastBitmapMetadata::read(&member.specific_data) {
+            Ok(metadata) => {
+                self.bitmap_meta.insert(member.slot, metadata);
+            }
+            Err(error) => eprintln!(
+                "skipping bitmap {}: {}",
+                member.number,
+                AssetParseError::truncated(self.dir, member.slot)(error)
+            ),
+        }
+    }
+
+    fn read_palette(&mut self, director_file: &mut MovieFile, member: &MacromediaCastMember) {
+        match read_palette_member(director_file, member) {
+            Ok(Some(palette)) => {
+                self.library.files.insert(
+                    member.number,
+                    MulleFile::MullePalette(MullePalette {
+                        name: member_name(member),
+                        palette,
+                    }),
+                );
+            }
+            Ok(None) => eprintln!("palette member {} has no CLUT", member.number),
+            Err(error) => eprintln!("skipping palette {}: {error}", member.number),
+        }
+    }
+
+    fn read_filmloop(
+        &mut self,
+        director_file: &mut MovieFile,
+        member: &MacromediaCastMember,
+        load_context: &mut LoadContext,
+    ) {
+        for (linked_type, linked_item) in director_file.linked_chunks(member) {
+            if linked_type != "SCVW" {
+                continue;
+            }
+            let frames = match director_file.read_chunk(linked_item).and_then(|data| {
+                score::read_frames(&data).map_err(AssetParseError::truncated(self.dir, linked_item))
+            }) {
+                Ok(frames) => frames,
+                Err(error) => {
+                    eprintln!("skipping filmloop {}: {error}", member.number);
+                    continue;
+                }
+            };
+            collect_inks(
+                &mut self.sprite_inks,
+                frames.iter().flat_map(|frame| &frame.sprites),
+                &self.own_casts,
+            );
+            let rect = read_filmloop_rect(&member.specific_data).unwrap_or_default();
+            self.library.files.insert(
+                member.number,
+                MulleFile::MulleFilmloop(MulleFilmloopMember {
+                    name: member_name(member),
+                    filmloop: load_context.add_labeled_asset(
+                        member.number.to_string(),
+                        MulleFilmloop {
+                            dir: self.dir.to_owned(),
+                            center: rect.center(),
+                            frames,
+                        },
+                    ),
+                }),
+            );
+        }
+    }
+
+    fn read_sound(
+        &mut self,
+        director_file: &mut MovieFile,
+        member: &MacromediaCastMember,
+        load_context: &mut LoadContext,
+    ) {
+        let num = member.number;
+        match read_sound_member(director_file, member) {
+            Ok(Some((sound, cue_points))) => {
+                self.library.files.insert(
+                    num,
+                    MulleFile::MulleSound(MulleSound {
+                        name: member_name(member),
+                        audio: load_context
+                            .add_labeled_asset(num.to_string(), MulleAudio::from(sound)),
+                        cue_points,
+                    }),
+                );
+            }
+            Ok(None) => eprintln!("sound member {num} has no samples"),
+            Err(error) => eprintln!("skipping sound {num}: {error}"),
+        }
+    }
+
+    fn read_shape(&mut self, member: &MacromediaCastMember) {
+        let shape = match shape::read_shape(&member.specific_data) {
+            Ok(shape) => shape,
+            Err(error) => {
+                eprintln!(
+                    "skipping shape {}: {}",
+                    member.number,
+                    AssetParseError::truncated(self.dir, member.slot)(error)
+                );
+                return;
+            }
+        };
+        self.library.files.insert(
+            member.number,
+            MulleFile::MulleImage(LazyImage::new(
+                member_name(member),
+                self.shared_dir.clone(),
+                member.slot,
+                ImageSource::Shape(shape),
+                self.cache.clone(),
+            )),
+        );
+    }
+
+    /// Reads a chunk of the member, if it is a kind that is decoded
+    fn read_chunk(
+        &mut self,
+        director_file: &mut MovieFile,
+        member: &MacromediaCastMember,
+        linked_type: &str,
+        linked_item: u32,
+    ) {
+        let num = member.number;
+        let kind = match (member.cast_type, linked_type) {
+            (1, "BITD") => "bitmap",
+            (3, "STXT") => "text",
+            (7, "STXT") => "button",
+            (12, "RTE1") => "rich text",
+            (2, "SCVW") => return, // filmloops were read before the bitmaps, for their inks
+            (4, "CLUT") => return, // palettes were read before the bitmaps
+            (12, "RTE0" | "RTE2") => return, // the styled text and its bitmap are not decoded
+            (_, "XMED") => {
+                eprintln!("skipping styled text {num}, text xtra members are not decoded");
+                return;
+            }
+            // (5)
+            // (9) ??
+            // (10) ??
+            // (11) ??
+            (cast_type, _) => {
+                eprintln!("unhandled file type of {linked_type} in cast_member {cast_type}");
+                return;
+            }
+        };
+        let data = match director_file.read_chunk(linked_item) {
+            Ok(data) => data,
+            Err(error) => {
+                eprintln!("skipping {kind} {num}: {error}");
+                return;
+            }
+        };
+        match member.cast_type {
+            1 => self.add_bitmap(member, linked_item, data),
+            3 => self.add_text(member, linked_item, data),
+            7 => self.add_button(member, linked_item, data),
+            _ => self.add_rich_text(member, linked_item, data),
+        }
+    }
+
+    fn add_bitmap(&mut self, member: &MacromediaCastMember, linked_item: u32, bitd: Vec<u8>) {
+        let num = member.number;
+        let Some(bitmap_meta) = self.bitmap_meta.get(&member.slot) else {
+            return; // the metadata was already reported as broken
+        };
+
+        let ink_transparency = u16::try_from(num).ok().and_then(|member| {
+            self.own_casts.iter().find_map(|cast_lib| {
+                self.sprite_inks
+                    .get(&MacromediaMemberRef {
+                        cast_lib: *cast_lib,
+                        member,
+                    })
+                    .and_then(|ink| ink.transparency())
+            })
+        });
+        let dir = self.dir;
+        let transparency_override = self
+            .transparency_overrides
+            .get(dir)
+            .and_then(|overrides| overrides.get(&num));
+        if let Some(transparency_override) = transparency_override {
+            if ink_transparency == Some(transparency_override.transparency) {
+                eprintln!(
+                    "the transparency override of {dir} member {num} ({}) is not needed, its ink says the same",
+                    transparency_override.reason
+                );
+            }
+        }
+        let transparency = transparency_override
+            .map(|transparency_override| transparency_override.transparency)
+            .or(ink_transparency)
+            .unwrap_or(Transparency::White);
+
+        // the pixels are decoded when the image is first used
+        let image = LazyImage::new(
+            member_name(member),
+            self.shared_dir.clone(),
+            linked_item,
+            ImageSource::Bitmap {
+                metadata: bitmap_meta.clone(),
+                bitd: bitd.into(),
+                transparency,
+            },
+            self.cache.clone(),
+        );
+        self.library.files.insert(num, MulleFile::MulleImage(image));
+    }
+
+    fn add_text(&mut self, member: &MacromediaCastMember, linked_item: u32, stxt: Vec<u8>) {
+        let num = member.number;
+        // the databases of the game are read right away, other texts when they are first used
+        if member
+            .name
+            .as_ref()
+            .is_some_and(|name| name.ends_with("DB"))
+        {
+            if self.parse_databases {
+                self.add_database(member, linked_item, &stxt);
+            } // otherwise they came from the cache
+            return;
+        }
+        self.library.files.insert(
+            num,
+            MulleFile::MulleText(LazyText::new(
+                member_name(member),
+                self.shared_dir.clone(),
+                linked_item,
+                TextSource::Styled(stxt.into()),
+                self.cache.clone(),
+            )),
+        );
+    }
+
+    fn add_database(&mut self, member: &MacromediaCastMember, linked_item: u32, stxt: &[u8]) {
+        let num = member.number;
+        let styled_text = match text::read_stxt(stxt) {
+            Ok(styled_text) => styled_text,
+            Err(error) => {
+                eprintln!(
+                    "skipping text {num}: {}",
+                    AssetParseError::truncated(self.dir, linked_item)(error)
+                );
+                return;
+            }
+        };
+        match try_get_mulledb(styled_text.text) {
+            Some(MulleDB::MapData(map)) => {
+                self.databases.map_db.insert(map.map_id, map);
+            }
+            Some(MulleDB::PartDB(part)) => {
+                self.databases.part_db.insert(part.part_id, part);
+            }
+            None => eprintln!(
+                "skipping database {num} {}, it does not parse",
+                member_name(member)
+            ),
+        }
+    }
+
+    fn add_rich_text(&mut self, member: &MacromediaCastMember, linked_item: u32, rte1: Vec<u8>) {
+        self.library.files.insert(
+            member.number,
+            MulleFile::MulleText(LazyText::new(
+                member_name(member),
+                self.shared_dir.clone(),
+                linked_item,
+                TextSource::Plain(rte1.into()),
+                self.cache.clone(),
+            )),
+        );
+    }
+
+    fn add_button(&mut self, member: &MacromediaCastMember, linked_item: u32, label: Vec<u8>) {
+        let button = match shape::read_button(&member.specific_data) {
+            Ok(button) => button,
+            Err(error) => {
+                eprintln!(
+                    "skipping button {}: {}",
+                    member.number,
+                    AssetParseError::truncated(self.dir, member.slot)(error)
+                );
+                return;
+            }
+        };
+        self.library.files.insert(
+            member.number,
+            MulleFile::MulleButton(LazyButton::new(
+                member_name(member),
+                self.shared_dir.clone(),
+                linked_item,
+                ButtonSource {
+                    button,
+                    label: label.into(),
+                },
+                self.cache.clone(),
+            )),
+        );
+    }
+}
+
+fn member_name(member: &MacromediaCastMember) -> String {
+    member.name.clone().unwrap_or_else(|| "default".to_owned())
+}
+
+/// The cast numbers of `library` that are the file itself, sprites of members in other casts are left to the
+/// file of that cast
+fn own_casts(dir: &str, library: &MulleLibrary) -> Vec<i16> {
+    std::iter::once(0) // files without a cast list do not number their casts
+        .chain(
+            library
+                .casts
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, cast_dir))| cast_dir == dir)
+                .filter_map(|(index, _)| i16::try_from(index + 1).ok()),
+        )
+        .collect()
+}
+
+/// Remembers the ink of every sprite that draws a member of this file
+fn collect_inks<'a>(
+    inks: &mut HashMap<MacromediaMemberRef, Ink>,
+    sprites: impl Iterator<Item = &'a MacromediaSprite>,
+    own_casts: &[i16],
+) {
+    for sprite in sprites {
+        if sprite.member != 0 && own_casts.contains(&sprite.cast_lib) {
+            inks.insert(sprite.member_ref(), sprite.ink);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use bevy::{asset::AssetHandleProvider, prelude::*};
+
+use super::{
+    decode_cache::{CachedImage, DecodeCache},
+    rgba_to_image, MulleButton, MulleImage, MulleText,
+};
+use crate::parsers::director::{
+    bitmap,
+    config::MovieConfig,
+    ink::Transparency,
+    palette::{self, MacromediaPalette},
+    shape::{MacromediaButton, MacromediaShape},
+    text::{self, MacromediaText},
+    AssetParseError, MacromediaCastBitmapMetadata,
+};
+
+// Loading a movie only reads the chunks of its members, the pixels and texts are decoded the first time a getter asks
+// for them and kept from then on. Most members of a file are never shown in a session, so most are never decoded
+// Decoded bitmaps and texts also go to the decode cache, so the next launch only has to read them back
+
+pub type LazyImage = LazyMember<MulleImage, ImageSource>;
+pub type LazyText = LazyMember<MulleText, TextSource>;
+pub type LazyButton = LazyMember<MulleButton, ButtonSource>;
+
+/// What an image is decoded from, the chunks are kept as they are in the file
+#[derive(Clone)]
+pub enum ImageSource {
+    Bitmap {
+        metadata: MacromediaCastBitmapMetadata,
+        bitd: Arc<[u8]>,
+        transparency: Transparency,
+    },
+    Shape(MacromediaShape),
+}
+
+#[derive(Clone)]
+pub enum TextSource {
+    Styled(Arc<[u8]>),
+    Plain(Arc<[u8]>),
+}
+
+#[derive(Clone)]
+pub struct ButtonSource {
+    pub button: MacromediaButton,
+    pub label: Arc<[u8]>,
+}
+
+/// A cast member that is decoded from `S` when it is first asked for
+#[derive(Clone)]
+pub struct LazyMember<T, S> {
+    pub(super) name: String,
+    /// The file and slot of the chunk the member is decoded from, for errors
+    dir: Arc<str>,
+    chunk: u32,
+    source: S,
+    cache: Arc<DecodeCache>,
+    decoded: OnceLock<Option<T>>,
+}
+
+impl<T, S> LazyMember<T, S> {
+    pub(super) const fn new(
+        name: String,
+        dir: Arc<str>,
+        chunk: u32,
+        source: S,
+        cache: Arc<DecodeCache>,
+    ) -> Self {
+        Self {
+            name,
+            dir,
+            chunk,
+            source,
+            cache,
+            decoded: OnceLock::new(),
+        }
+    }
+
+    /// A member that failed to decode is reported once and stays `None`
+    fn get_or_decode(&self, decode: impl FnOnce(&S) -> Result<T, AssetParseError>) -> Option<&T> {
+        self.decoded
+            .get_or_init(|| {
+                decode(&self.source)
+                    .inspect_err(|error| eprintln!("skipping member {}: {error}", self.name))
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    fn read_text(&self, stxt: &[u8]) -> Result<MacromediaText, AssetParseError> {
+        text::read_stxt(stxt).map_err(AssetParseError::truncated(&self.dir, self.chunk))
+    }
+
+    fn text(text: MacromediaText) -> MulleText {
+        MulleText {
+            text: text.text,
+            runs: text.runs,
+        }
+    }
+}
+
+impl LazyImage {
+    /// `palettes` looks up palette members by cast library and number
+    pub(super) fn get<'a>(
+        &self,
+        config: Option<&MovieConfig>,
+        palettes: impl Fn(i16, u32) -> Option<&'a MacromediaPalette>,
+        images: &DecodedImages,
+    ) -> Option<&MulleImage> {
+        self.get_or_decode(|source| {
+            let (metadata, rgba_data) = match source {
+                ImageSource::Bitmap {
+                    metadata,
+                    bitd,
+                    transparency,
+                } => {
+                    let rgba_data = match self.cache.read::<CachedImage>(self.chunk) {
+                        Some(cached) if cached.transparency == *transparency => cached.rgba,
+                        _ => {
+                            let palette =
+                                metadata
+                                    .palette
+                                    .resolve(metadata.bit_depth(), config, palettes);
+                            let cached = CachedImage {
+                                transparency: *transparency,
+                                rgba: bitmap::decode_bitd(metadata, bitd, palette, *transparency),
+                            };
+                            self.cache.write(self.chunk, &cached);
+                            cached.rgba
+                        }
+                    };
+                    (metadata.clone(), rgba_data)
+                }
+                ImageSource::Shape(shape) => {
+                    let palette = palette::default_palette(config, palettes);
+                    (
+                        MacromediaCastBitmapMetadata::generated(shape.width, shape.height),
+                        shape.rasterize(palette),
+                    )
+                }
+            };
+            Ok(MulleImage {
+                sprite: Sprite::from_image(images.add(rgba_to_image(&metadata, rgba_data))),
+                bitmap_metadata: metadata,
+            })
+        })
+    }
+}
+
+impl LazyText {
+    pub(super) fn get(&self) -> Option<&MulleText> {
+        self.get_or_decode(|source| {
+            if let Some(text) = self.cache.read(self.chunk) {
+                return Ok(Self::text(text));
+            }
+            let text = match source {
+                TextSource::Styled(stxt) => self.read_text(stxt)?,
+                TextSource::Plain(rte1) => text::read_rte1(rte1),
+            };
+            self.cache.write(self.chunk, &text);
+            Ok(Self::text(text))
+        })
+    }
+}
+
+impl LazyButton {
+    pub(super) fn get(&self, images: &DecodedImages) -> Option<&MulleButton> {
+        self.get_or_decode(|ButtonSource { button, label }| {
+            let label = self.read_text(label)?;
+            let metadata = MacromediaCastBitmapMetadata::generated(button.width, button.height);
+            let rgba_data = button.rasterize();
+            Ok(MulleButton {
+                image: MulleImage {
+                    sprite: Sprite::from_image(images.add(rgba_to_image(&metadata, rgba_data))),
+                    bitmap_metadata: metadata,
+                },
+                label: Self::text(label),
+            })
+        })
+    }
+}
+
+/// The getters can not add to the image assets, so images they decode get a reserved handle and wait here
+/// until `upload_decoded_images` adds them at the end of the frame
+pub(super) struct DecodedImages {
+    handles: AssetHandleProvider,
+    pending: Mutex<Vec<(Handle<Image>, Image)>>,
+}
+
+impl DecodedImages {
+    pub(super) const fn new(handles: AssetHandleProvider) -> Self {
+        Self {
+            handles,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn add(&self, image: Image) -> Handle<Image> {
+        let handle = self.handles.reserve_handle().typed::<Image>();
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((handle.clone(), image));
+        handle
+    }
+
+    pub(super) fn take(&self) -> Vec<(Handle<Image>, Image)> {
+        std::mem::take(&mut *self.pending.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::{prelude::*, utils::hashbrown::HashMap};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     despawn_screen, parsers::database_language::Point, render::scaler::PIXEL_PERFECT_LAYERS,
@@ -6,15 +7,18 @@ use crate::{
 };
 
 use super::{
-    mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper},
+    mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper, MulleAssetState},
     mulle_point_and_click::MulleDraggable,
 };
 
+// the car is built in the garage, the views of the parts are found through the casts it links
+pub const CAR_MOVIE: &str = "03.dxr";
+
 pub struct MulleCarPlugin;
 
 impl Plugin for MulleCarPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init_car)
+        app.add_systems(OnEnter(MulleAssetState::Loaded), init_car)
             .add_systems(OnEnter(GameState::GarageWithCar), spawn_car_parts)
             .add_systems(OnEnter(GameState::YardWithCar), spawn_car_parts)
             .add_systems(
@@ -80,10 +84,10 @@ fn spawn_car_parts(car: Res<Car>, mut commands: Commands, mulle_asset_helper: Re
                 continue;
             }
             let image = mulle_asset_helper
-                .get_mulle_image_by_name("cddata.cxt".to_owned(), use_view.to_string())
+                .get_mulle_image_by_name(CAR_MOVIE.to_owned(), use_view.to_string())
                 .unwrap();
             let image_junk = mulle_asset_helper
-                .get_mulle_image_by_name("cddata.cxt".to_owned(), part.junk_view.to_string())
+                .get_mulle_image_by_name(CAR_MOVIE.to_owned(), part.junk_view.to_string())
                 .cloned();
             let rect = Rect::new(
                 f32::from(-image.bitmap_metadata.image_reg_x) + 40.,
@@ -234,7 +238,7 @@ impl Car {
         from_location: &PartLocation,
         to_location: &PartLocation,
         _location: Option<Point>,
-    ) -> Result<(), MulleCarError> {
+    ) -> Result<(), MulleCarError<'_>> {
         println!(
             "Moving part {part_id} from {:?} to {:?}",
             from_location, to_location
@@ -297,7 +301,7 @@ impl Car {
     // }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct PartDB {
     pub part_id: i32,
@@ -313,7 +317,7 @@ pub struct PartDB {
     pub covers: Vec<String>,
     pub new: Vec<PartNew>,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct PartNew {
     pub tag: String,
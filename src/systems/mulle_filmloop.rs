@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+
+use crate::{
+    parsers::director::ink::Ink,
+    render::scaler::PIXEL_PERFECT_LAYERS,
+    systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper, MulleFilmloop},
+};
+
+// Frames per second when neither the filmloop nor the movie sets a tempo
+const DEFAULT_TEMPO: u8 = 15;
+
+pub struct MulleFilmloopPlugin;
+
+impl Plugin for MulleFilmloopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, play_filmloops);
+    }
+}
+
+/// Plays a filmloop in a loop, the sprites of the current frame are spawned as children
+/// the transform of this entity is where the middle of the filmloop ends up
+#[derive(Component)]
+#[require(Transform, Visibility)]
+pub struct MulleFilmloopPlayer {
+    filmloop: Handle<MulleFilmloop>,
+    frame: usize,
+    shown_frame: Option<usize>,
+    tempo: u8,
+    timer: Timer,
+}
+
+impl MulleFilmloopPlayer {
+    pub fn new(filmloop: Handle<MulleFilmloop>) -> Self {
+        Self {
+            filmloop,
+            frame: 0,
+            shown_frame: None,
+            tempo: DEFAULT_TEMPO,
+            timer: Timer::from_seconds(1. / f32::from(DEFAULT_TEMPO), TimerMode::Once),
+        }
+    }
+}
+
+fn play_filmloops(
+    mut commands: Commands,
+    time: Res<Time>,
+    filmloops: Res<Assets<MulleFilmloop>>,
+    mulle_asset_helper: Res<MulleAssetHelp>,
+    mut players: Query<(Entity, &mut MulleFilmloopPlayer)>,
+) {
+    for (entity, mut player) in &mut players {
+        let Some(filmloop) = filmloops.get(&player.filmloop) else {
+            continue;
+        };
+        if filmloop.frames.is_empty() {
+            continue;
+        }
+
+        if player.timer.tick(time.delta()).finished() {
+            player.frame = (player.frame + 1) % filmloop.frames.len();
+        }
+        if player.shown_frame == Some(player.frame) {
+            continue;
+        }
+
+        if player.shown_frame.is_none() {
+            // the filmloop plays at the tempo of the movie until one of its frames sets another
+            if let Some(config) = mulle_asset_helper.get_movie_config(filmloop.dir.clone()) {
+                if config.tempo != 0 {
+                    player.tempo = config.tempo;
+                }
+            }
+        }
+        let frame = &filmloop.frames[player.frame];
+        if frame.tempo != 0 {
+            player.tempo = frame.tempo;
+        }
+        let tempo = player.tempo;
+        player
+            .timer
+            .set_duration(std::time::Duration::from_secs_f32(1. / f32::from(tempo)));
+        player.timer.reset();
+        player.shown_frame = Some(player.frame);
+
+        commands.entity(entity).despawn_descendants();
+        commands.entity(entity).with_children(|parent| {
+            for sprite in &frame.sprites {
+                let Some(image) = mulle_asset_helper
+                    .get_mulle_image_by_asset_number(filmloop.dir.clone(), sprite.member_number())
+                else {
+                    continue; // shapes and members of other types are not drawn yet
+                };
+                let metadata = &image.bitmap_metadata;
+                let mut bevy_sprite = image.sprite.clone();
+
+                // the sprite can be stretched, the registration point stretches with it
+                let (width, height) = if sprite.width > 0 && sprite.height > 0 {
+                    (sprite.width, sprite.height)
+                } else {
+                    (metadata.image_width, metadata.image_height)
+                };
+                let scale_x = f32::from(width) / f32::from(metadata.image_width.max(1));
+                let scale_y = f32::from(height) / f32::from(metadata.image_height.max(1));
+                if (width, height) != (metadata.image_width, metadata.image_height) {
+                    bevy_sprite.custom_size = Some(Vec2::new(f32::from(width), f32::from(height)));
+                }
+                if sprite.ink == Ink::Blend {
+                    bevy_sprite.color =
+                        Color::srgba(1., 1., 1., 1. - f32::from(sprite.blend) / 255.);
+                }
+
+                // director counts y downwards, the registration point of the member sits on the position of the sprite
+                let reg_x = f32::from(metadata.image_reg_x) * scale_x;
+                let reg_y = f32::from(metadata.image_reg_y) * scale_y;
+                let x = f32::from(sprite.pos_x) - filmloop.center.x + f32::from(width) / 2. - reg_x;
+                let y =
+                    filmloop.center.y - f32::from(sprite.pos_y) - f32::from(height) / 2. + reg_y;
+
+                parent.spawn((
+                    bevy_sprite,
+                    Transform::from_xyz(x, y, f32::from(sprite.channel) * 0.001),
+                    PIXEL_PERFECT_LAYERS,
+                ));
+            }
+        });
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::{audio::PlaybackMode, prelude::*};
+
+use crate::{
+    despawn_screen,
+    render::scaler::PIXEL_PERFECT_LAYERS,
+    screens::yard::RoomState,
+    systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper},
+};
+
+mod error;
+pub mod vm;
+
+use vm::{LingoEffect, LingoMovie, LingoRuntime, LingoValue};
+
+// Runs original lingo handlers next to the hand written rooms, a click can call a handler by name
+// and whatever the handler does to sprites and sounds is mirrored onto entities
+
+// the stage position that ends up in the middle of the screen when the movie has no config
+const STAGE_CENTER: Vec2 = Vec2::new(320., 240.);
+// puppet sprites go on top of the room
+const PUPPET_LAYER: f32 = 5.;
+
+pub struct MulleLingoPlugin;
+
+impl Plugin for MulleLingoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MulleLingo>()
+            .add_event::<LingoCall>()
+            .add_systems(
+                PreUpdate,
+                despawn_screen::<MulleLingoSprite>.run_if(resource_exists_and_changed::<RoomState>),
+            )
+            .add_systems(Update, (tick_lingo_timeouts, run_lingo_calls).chain());
+    }
+}
+
+/// Asks for the handler `handler` of the scripts in `dir` to run
+#[derive(Event, Clone, Debug)]
+pub struct LingoCall {
+    pub dir: String,
+    pub handler: String,
+    pub arguments: Vec<LingoValue>,
+}
+
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct MulleLingo(LingoRuntime);
+
+/// A sprite channel that lingo puppeted
+#[derive(Component)]
+struct MulleLingoSprite {
+    channel: u16,
+}
+
+#[derive(Component)]
+struct MulleLingoSound {
+    channel: u16,
+}
+
+fn tick_lingo_timeouts(
+    time: Res<Time>,
+    mut lingo: ResMut<MulleLingo>,
+    mut calls: EventWriter<LingoCall>,
+) {
+    for timeout in lingo.advance(time.delta().as_millis() as u64) {
+        calls.send(LingoCall {
+            dir: timeout.dir,
+            handler: timeout.handler,
+            arguments: vec![timeout.target],
+        });
+    }
+}
+
+fn run_lingo_calls(
+    mut commands: Commands,
+    mut calls: EventReader<LingoCall>,
+    mut lingo: ResMut<MulleLingo>,
+    mulle_asset_helper: Res<MulleAssetHelp>,
+    sprites: Query<(Entity, &MulleLingoSprite)>,
+    sounds: Query<(Entity, &MulleLingoSound)>,
+) {
+    for call in calls.read() {
+        let Some(context) = mulle_asset_helper.get_lingo_context(call.dir.clone()) else {
+            eprintln!("{} has no scripts to run {}", call.dir, call.handler);
+            continue;
+        };
+        let member_number = |name: &str, cast: Option<&str>| {
+            cast.map_or_else(
+                || mulle_asset_helper.get_asset_number_by_name(call.dir.clone(), name.to_owned()),
+                |cast| {
+                    mulle_asset_helper.get_asset_number_in_cast(
+                        call.dir.clone(),
+                        cast.to_owned(),
+                        name.to_owned(),
+                    )
+                },
+            )
+        };
+        let movie = LingoMovie {
+            dir: &call.dir,
+            context,
+            member_number: &member_number,
+        };
+        if let Err(error) = lingo.call(&movie, &call.handler, call.arguments.clone()) {
+            eprintln!("lingo {}: {error}", call.dir);
+        }
+
+        let mut changed_channels = Vec::new();
+        for effect in lingo.take_effects() {
+            match effect {
+                LingoEffect::SpriteChanged(channel) => {
+                    if !changed_channels.contains(&channel) {
+                        changed_channels.push(channel);
+                    }
+                }
+                LingoEffect::PlaySound { channel, member } => {
+                    for (entity, sound) in &sounds {
+                        if sound.channel == channel {
+                            commands.entity(entity).despawn();
+                        }
+                    }
+                    let Some(sound) = mulle_asset_helper
+                        .get_mulle_sound_by_asset_number(call.dir.clone(), member)
+                    else {
+                        eprintln!("lingo {}: member {member} is not a sound", call.dir);
+                        continue;
+                    };
+                    commands.spawn((
+                        AudioPlayer(sound.audio.clone()),
+                        PlaybackSettings {
+                            mode: PlaybackMode::Despawn,
+                            ..default()
+                        },
+                        MulleLingoSound { channel },
+                    ));
+                }
+                LingoEffect::StopSound { channel } => {
+                    for (entity, sound) in &sounds {
+                        if sound.channel == channel {
+                            commands.entity(entity).despawn();
+                        }
+                    }
+                }
+            }
+        }
+
+        // puppet sprites are placed relative to the middle of the stage
+        let stage_center = mulle_asset_helper
+            .get_movie_config(call.dir.clone())
+            .map_or(STAGE_CENTER, |config| {
+                Vec2::new(
+                    f32::from(config.stage_width()) / 2.,
+                    f32::from(config.stage_height()) / 2.,
+                )
+            });
+
+        // a handler often moves a sprite several times, only where it ended up matters
+        for channel in changed_channels {
+            for (entity, sprite) in &sprites {
+                if sprite.channel == channel {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            let Some(state) = lingo.sprites.get(&channel) else {
+                continue;
+            };
+            if !state.puppet || !state.visible {
+                continue;
+            }
+            let Some(image) = state.member.and_then(|member| {
+                mulle_asset_helper.get_mulle_image_by_asset_number(call.dir.clone(), member)
+            }) else {
+                continue;
+            };
+            // the registration point of the member goes on the loc of the sprite
+            let metadata = &image.bitmap_metadata;
+            let x = state.loc_h as f32 - stage_center.x + f32::from(metadata.image_width) / 2.
+                - f32::from(metadata.image_reg_x);
+            let y = stage_center.y - state.loc_v as f32 - f32::from(metadata.image_height) / 2.
+                + f32::from(metadata.image_reg_y);
+            let z = f32::from(channel).mul_add(0.001, PUPPET_LAYER);
+            commands.spawn((
+                image.sprite.clone(),
+                Transform::from_xyz(x, y, z),
+                MulleLingoSprite { channel },
+                PIXEL_PERFECT_LAYERS,
+            ));
+        }
+    }
+}
@@ -0,0 +1,47 @@
+/// Everything that stops a lingo handler, the handler is abandoned but the game goes on
+pub enum LingoError {
+    UnknownHandler { name: String },
+    UnknownMember { name: String },
+    StackUnderflow { handler: String },
+    TooDeep { handler: String },
+    TooLong { handler: String },
+    BadVariable { handler: String, index: usize },
+    OutOfRange { handler: String, index: usize },
+    Unsupported { handler: String, what: String },
+}
+
+impl std::fmt::Display for LingoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownHandler { name } => write!(f, "no handler or builtin called {name}"),
+            Self::UnknownMember { name } => write!(f, "no cast member called {name}"),
+            Self::StackUnderflow { handler } => {
+                write!(f, "{handler}: popped more values than were pushed")
+            }
+            Self::TooDeep { handler } => write!(f, "{handler}: too many nested calls"),
+            Self::TooLong { handler } => {
+                write!(
+                    f,
+                    "{handler}: ran too long, it is probably waiting for something"
+                )
+            }
+            Self::BadVariable { handler, index } => {
+                write!(f, "{handler}: variable {index} is not declared")
+            }
+            Self::OutOfRange { handler, index } => {
+                write!(f, "{handler}: index {index} is too far out")
+            }
+            Self::Unsupported { handler, what } => {
+                write!(f, "{handler}: {what} is not supported yet")
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for LingoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl std::error::Error for LingoError {}
@@ -0,0 +1,1432 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::parsers::director::lingo::{
+    LingoContext, LingoHandler, LingoInstruction, LingoLiteral, LingoOpcode, LingoScript,
+};
+
+use super::error::LingoError;
+
+// A small lingo interpreter, it runs the decoded bytecode of a handler on a stack
+// Everything a handler does to the stage is kept in `LingoRuntime` and reported as effects,
+// the bevy side decides what those mean for the entities
+// Script instances are not supported, every script has one set of properties shared by all its uses
+
+// deep enough for the recursion of the original scripts, shallow enough to stop a runaway one
+const MAX_CALL_DEPTH: usize = 64;
+
+// a call from the game runs at most this many instructions, handlers that wait in a loop for something
+// outside the vm (a sound, a timer) would otherwise never give the game back
+const MAX_INSTRUCTIONS: usize = 1_000_000;
+
+// lists and arguments grow to fit whatever index is set, a bad index in a script should not take all memory
+const MAX_LIST_LENGTH: usize = 65_536;
+
+#[derive(Clone, Debug, Default)]
+pub enum LingoValue {
+    #[default]
+    Void,
+    Int(i32),
+    Float(f64),
+    String(String),
+    Symbol(String),
+    Point(i32, i32),
+    /// Lists are shared, changing one through a variable changes it everywhere
+    List(Arc<Mutex<Vec<Self>>>),
+    PropList(Arc<Mutex<Vec<(Self, Self)>>>),
+    /// The arguments of a call while they are on the stack
+    ArgList {
+        values: Vec<Self>,
+        returns: bool,
+    },
+    Sprite(u16),
+    Member(u32),
+    /// `me` inside the handlers of a script
+    Script(u32),
+    Timeout(String),
+}
+
+/// What lingo knows about a sprite channel
+#[derive(Clone, Debug)]
+pub struct LingoSprite {
+    pub member: Option<u32>,
+    pub loc_h: i32,
+    pub loc_v: i32,
+    pub visible: bool,
+    /// Puppeted sprites are controlled by lingo instead of the score
+    pub puppet: bool,
+}
+
+impl Default for LingoSprite {
+    fn default() -> Self {
+        Self {
+            member: None,
+            loc_h: 0,
+            loc_v: 0,
+            visible: true,
+            puppet: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LingoTimeout {
+    pub name: String,
+    /// The movie the handler is in
+    pub dir: String,
+    pub period: u64,
+    pub handler: String,
+    pub target: LingoValue,
+    elapsed: u64,
+}
+
+/// Things a handler did that the game has to act on
+#[derive(Clone, Debug)]
+pub enum LingoEffect {
+    SpriteChanged(u16),
+    PlaySound { channel: u16, member: u32 },
+    StopSound { channel: u16 },
+}
+
+/// The movie a handler runs in
+pub struct LingoMovie<'a> {
+    pub dir: &'a str,
+    pub context: &'a LingoContext,
+    /// Looks up the number of a cast member by name, in the cast with the given name or else in all of them
+    /// members of other casts than the first are numbered cast * 65536 + member
+    pub member_number: &'a dyn Fn(&str, Option<&str>) -> Option<u32>,
+}
+
+#[derive(Default)]
+pub struct LingoRuntime {
+    pub globals: HashMap<String, LingoValue>,
+    pub sprites: HashMap<u16, LingoSprite>,
+    pub timeouts: Vec<LingoTimeout>,
+    pub milliseconds: u64,
+    effects: Vec<LingoEffect>,
+    properties: HashMap<(String, u32), HashMap<String, LingoValue>>,
+    random_state: u32,
+    /// What is left of `MAX_INSTRUCTIONS` for the call that is running
+    instructions_left: usize,
+}
+
+struct CallFrame {
+    stack: Vec<LingoValue>,
+    arguments: Vec<LingoValue>,
+    locals: Vec<LingoValue>,
+}
+
+enum Flow {
+    Next,
+    Jump(i32),
+    Return(LingoValue),
+}
+
+impl LingoRuntime {
+    /// Runs the handler called `handler` of the movie, the scripts are searched in order of their number
+    pub fn call(
+        &mut self,
+        movie: &LingoMovie,
+        handler: &str,
+        arguments: Vec<LingoValue>,
+    ) -> Result<LingoValue, LingoError> {
+        let (script_number, script, found) = find_handler(movie.context, handler, None)
+            .ok_or_else(|| LingoError::UnknownHandler {
+                name: handler.to_owned(),
+            })?;
+        self.instructions_left = MAX_INSTRUCTIONS;
+        self.run(movie, script_number, script, found, arguments, 0)
+    }
+
+    /// Everything that happened since the last time, in order
+    pub fn take_effects(&mut self) -> Vec<LingoEffect> {
+        std::mem::take(&mut self.effects)
+    }
+
+    /// Moves the clock forward and returns the timeouts that went off
+    pub fn advance(&mut self, milliseconds: u64) -> Vec<LingoTimeout> {
+        self.milliseconds += milliseconds;
+        let mut due = Vec::new();
+        for timeout in &mut self.timeouts {
+            timeout.elapsed += milliseconds;
+            if timeout.period > 0 && timeout.elapsed >= timeout.period {
+                timeout.elapsed %= timeout.period;
+                due.push(timeout.clone());
+            }
+        }
+        due
+    }
+
+    fn run(
+        &mut self,
+        movie: &LingoMovie,
+        script_number: u32,
+        script: &LingoScript,
+        handler: &LingoHandler,
+        arguments: Vec<LingoValue>,
+        depth: usize,
+    ) -> Result<LingoValue, LingoError> {
+        if depth > MAX_CALL_DEPTH {
+            return Err(LingoError::TooDeep {
+                handler: handler.name.clone(),
+            });
+        }
+        let mut frame = CallFrame {
+            stack: Vec::new(),
+            arguments,
+            locals: vec![LingoValue::Void; handler.locals.len()],
+        };
+
+        let mut index = 0;
+        while let Some(instruction) = handler.instructions.get(index) {
+            index += 1;
+            self.instructions_left =
+                self.instructions_left
+                    .checked_sub(1)
+                    .ok_or_else(|| LingoError::TooLong {
+                        handler: handler.name.clone(),
+                    })?;
+            match self.step(
+                movie,
+                script_number,
+                script,
+                handler,
+                &mut frame,
+                instruction,
+                depth,
+            )? {
+                Flow::Next => {}
+                Flow::Jump(position) => {
+                    index = handler
+                        .instructions
+                        .partition_point(|other| (other.position as i32) < position);
+                }
+                Flow::Return(value) => return Ok(value),
+            }
+        }
+        Ok(LingoValue::Void)
+    }
+
+    #[allow(clippy::too_many_lines, clippy::too_many_arguments)]
+    fn step(
+        &mut self,
+        movie: &LingoMovie,
+        script_number: u32,
+        script: &LingoScript,
+        handler: &LingoHandler,
+        frame: &mut CallFrame,
+        instruction: &LingoInstruction,
+        depth: usize,
+    ) -> Result<Flow, LingoError> {
+        let operand = instruction.operand;
+        let name = || movie.context.name(operand);
+        let underflow = || LingoError::StackUnderflow {
+            handler: handler.name.clone(),
+        };
+        let unsupported = |what: String| LingoError::Unsupported {
+            handler: handler.name.clone(),
+            what,
+        };
+        macro_rules! pop {
+            () => {
+                frame.stack.pop().ok_or_else(underflow)?
+            };
+        }
+
+        match instruction.opcode {
+            LingoOpcode::Return | LingoOpcode::ReturnFactory => {
+                return Ok(Flow::Return(frame.stack.pop().unwrap_or_default()));
+            }
+            LingoOpcode::PushZero => frame.stack.push(LingoValue::Int(0)),
+            LingoOpcode::Multiply
+            | LingoOpcode::Add
+            | LingoOpcode::Subtract
+            | LingoOpcode::Divide
+            | LingoOpcode::Modulo => {
+                let right = pop!();
+                let left = pop!();
+                frame
+                    .stack
+                    .push(arithmetic(instruction.opcode, &left, &right));
+            }
+            LingoOpcode::Negate => {
+                let value = pop!();
+                frame.stack.push(match value {
+                    LingoValue::Float(float) => LingoValue::Float(-float),
+                    other => LingoValue::Int(other.as_int().wrapping_neg()),
+                });
+            }
+            LingoOpcode::JoinString | LingoOpcode::JoinPaddedString => {
+                let right = pop!().to_text();
+                let left = pop!().to_text();
+                let separator = if instruction.opcode == LingoOpcode::JoinPaddedString {
+                    " "
+                } else {
+                    ""
+                };
+                frame
+                    .stack
+                    .push(LingoValue::String(format!("{left}{separator}{right}")));
+            }
+            LingoOpcode::LessThan
+            | LingoOpcode::LessThanOrEqual
+            | LingoOpcode::NotEqual
+            | LingoOpcode::Equal
+            | LingoOpcode::GreaterThan
+            | LingoOpcode::GreaterThanOrEqual => {
+                let right = pop!();
+                let left = pop!();
+                let result = match instruction.opcode {
+                    LingoOpcode::Equal => left.lingo_eq(&right),
+                    LingoOpcode::NotEqual => !left.lingo_eq(&right),
+                    LingoOpcode::LessThan => left.as_float() < right.as_float(),
+                    LingoOpcode::LessThanOrEqual => left.as_float() <= right.as_float(),
+                    LingoOpcode::GreaterThan => left.as_float() > right.as_float(),
+                    _ => left.as_float() >= right.as_float(),
+                };
+                frame.stack.push(LingoValue::from(result));
+            }
+            LingoOpcode::And => {
+                let right = pop!();
+                let left = pop!();
+                frame
+                    .stack
+                    .push(LingoValue::from(left.is_truthy() && right.is_truthy()));
+            }
+            LingoOpcode::Or => {
+                let right = pop!();
+                let left = pop!();
+                frame
+                    .stack
+                    .push(LingoValue::from(left.is_truthy() || right.is_truthy()));
+            }
+            LingoOpcode::Not => {
+                let value = pop!();
+                frame.stack.push(LingoValue::from(!value.is_truthy()));
+            }
+            LingoOpcode::ContainsString | LingoOpcode::StartsWithString => {
+                let needle = pop!().to_text().to_lowercase();
+                let haystack = pop!().to_text().to_lowercase();
+                let result = if instruction.opcode == LingoOpcode::ContainsString {
+                    haystack.contains(&needle)
+                } else {
+                    haystack.starts_with(&needle)
+                };
+                frame.stack.push(LingoValue::from(result));
+            }
+            LingoOpcode::PushList => {
+                let values = pop!().into_arguments();
+                frame.stack.push(LingoValue::list(values));
+            }
+            LingoOpcode::PushPropertyList => {
+                let values = pop!().into_arguments();
+                let pairs = values
+                    .chunks_exact(2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect();
+                frame
+                    .stack
+                    .push(LingoValue::PropList(Arc::new(Mutex::new(pairs))));
+            }
+            LingoOpcode::Swap => {
+                let top = pop!();
+                let below = pop!();
+                frame.stack.push(top);
+                frame.stack.push(below);
+            }
+            LingoOpcode::PushInt8 | LingoOpcode::PushInt16 | LingoOpcode::PushInt32 => {
+                frame.stack.push(LingoValue::Int(operand));
+            }
+            LingoOpcode::PushFloat32 => {
+                frame
+                    .stack
+                    .push(LingoValue::Float(f64::from(f32::from_bits(operand as u32))));
+            }
+            LingoOpcode::PushArgList | LingoOpcode::PushArgListNoReturn => {
+                let count = operand.max(0) as usize;
+                if frame.stack.len() < count {
+                    return Err(underflow());
+                }
+                let values = frame.stack.split_off(frame.stack.len() - count);
+                frame.stack.push(LingoValue::ArgList {
+                    values,
+                    returns: instruction.opcode == LingoOpcode::PushArgList,
+                });
+            }
+            LingoOpcode::PushConstant => {
+                let literal = script
+                    .literals
+                    .get(movie.context.variable_index(operand))
+                    .cloned();
+                frame.stack.push(match literal {
+                    Some(LingoLiteral::String(text)) => LingoValue::String(text),
+                    Some(LingoLiteral::Int(int)) => LingoValue::Int(int),
+                    Some(LingoLiteral::Float(float)) => LingoValue::Float(float),
+                    Some(LingoLiteral::Unknown(_)) | None => LingoValue::Void,
+                });
+            }
+            LingoOpcode::PushSymbol => frame.stack.push(LingoValue::Symbol(name())),
+            LingoOpcode::GetGlobal | LingoOpcode::GetGlobal2 => {
+                let value = self
+                    .globals
+                    .get(&name().to_lowercase())
+                    .cloned()
+                    .unwrap_or_default();
+                frame.stack.push(value);
+            }
+            LingoOpcode::SetGlobal | LingoOpcode::SetGlobal2 => {
+                let value = pop!();
+                self.globals.insert(name().to_lowercase(), value);
+            }
+            LingoOpcode::GetProperty => {
+                let value = self
+                    .script_properties(movie, script_number)
+                    .get(&name().to_lowercase())
+                    .cloned()
+                    .unwrap_or_default();
+                frame.stack.push(value);
+            }
+            LingoOpcode::SetProperty => {
+                let value = pop!();
+                self.script_properties(movie, script_number)
+                    .insert(name().to_lowercase(), value);
+            }
+            LingoOpcode::GetParameter => {
+                let index = movie.context.variable_index(operand);
+                frame
+                    .stack
+                    .push(frame.arguments.get(index).cloned().unwrap_or_default());
+            }
+            LingoOpcode::SetParameter => {
+                let index = movie.context.variable_index(operand);
+                let value = pop!();
+                // a call can pass fewer arguments than the handler has, but not more variables than it declares
+                if index >= frame.arguments.len().max(handler.arguments.len())
+                    || index >= MAX_LIST_LENGTH
+                {
+                    return Err(LingoError::BadVariable {
+                        handler: handler.name.clone(),
+                        index,
+                    });
+                }
+                if frame.arguments.len() <= index {
+                    frame.arguments.resize(index + 1, LingoValue::Void);
+                }
+                frame.arguments[index] = value;
+            }
+            LingoOpcode::GetLocal => {
+                let index = movie.context.variable_index(operand);
+                frame
+                    .stack
+                    .push(frame.locals.get(index).cloned().unwrap_or_default());
+            }
+            LingoOpcode::SetLocal => {
+                let index = movie.context.variable_index(operand);
+                let value = pop!();
+                let Some(local) = frame.locals.get_mut(index) else {
+                    return Err(LingoError::BadVariable {
+                        handler: handler.name.clone(),
+                        index,
+                    });
+                };
+                *local = value;
+            }
+            LingoOpcode::Jump => return Ok(Flow::Jump(instruction.position as i32 + operand)),
+            LingoOpcode::EndRepeat => {
+                return Ok(Flow::Jump(instruction.position as i32 - operand));
+            }
+            LingoOpcode::JumpIfZero => {
+                if !pop!().is_truthy() {
+                    return Ok(Flow::Jump(instruction.position as i32 + operand));
+                }
+            }
+            LingoOpcode::LocalCall => {
+                let (arguments, returns) = pop!().into_call();
+                let called = script
+                    .handlers
+                    .get(operand.max(0) as usize)
+                    .ok_or_else(|| unsupported(format!("local handler {operand}")))?;
+                let result =
+                    self.run(movie, script_number, script, called, arguments, depth + 1)?;
+                if returns {
+                    frame.stack.push(result);
+                }
+            }
+            LingoOpcode::ExternalCall => {
+                let (arguments, returns) = pop!().into_call();
+                let called = name();
+                let result = match find_handler(movie.context, &called, Some(script_number)) {
+                    Some((number, found_script, found)) => {
+                        self.run(movie, number, found_script, found, arguments, depth + 1)?
+                    }
+                    None => self.builtin(movie, &called, arguments, depth)?,
+                };
+                if returns {
+                    frame.stack.push(result);
+                }
+            }
+            LingoOpcode::ObjectCall => {
+                let (arguments, returns) = pop!().into_call();
+                let result = self.object_call(movie, &name(), arguments, depth)?;
+                if returns {
+                    frame.stack.push(result);
+                }
+            }
+            LingoOpcode::GetMovieProperty => {
+                let value = match name().to_lowercase().as_str() {
+                    "milliseconds" => LingoValue::Int(self.milliseconds as i32),
+                    other => return Err(unsupported(format!("the {other}"))),
+                };
+                frame.stack.push(value);
+            }
+            LingoOpcode::GetObjectProperty => {
+                let object = pop!();
+                frame
+                    .stack
+                    .push(self.get_object_property(&object, &name())?);
+            }
+            LingoOpcode::SetObjectProperty => {
+                let value = pop!();
+                let object = pop!();
+                self.set_object_property(movie, &object, &name(), value)?;
+            }
+            LingoOpcode::Peek => {
+                let value = frame
+                    .stack
+                    .iter()
+                    .rev()
+                    .nth(operand.max(0) as usize)
+                    .cloned()
+                    .ok_or_else(underflow)?;
+                frame.stack.push(value);
+            }
+            LingoOpcode::Pop => {
+                for _ in 0..operand.max(0) {
+                    pop!();
+                }
+            }
+            LingoOpcode::TheBuiltin => {
+                pop!(); // the empty argument list
+                return Err(unsupported(format!("the {}", name())));
+            }
+            other => return Err(unsupported(other.name().to_owned())),
+        }
+        Ok(Flow::Next)
+    }
+
+    fn script_properties(
+        &mut self,
+        movie: &LingoMovie,
+        script_number: u32,
+    ) -> &mut HashMap<String, LingoValue> {
+        self.properties
+            .entry((movie.dir.to_owned(), script_number))
+            .or_default()
+    }
+
+    fn member(&self, movie: &LingoMovie, value: &LingoValue) -> Result<u32, LingoError> {
+        match value {
+            LingoValue::Member(number) => Ok(*number),
+            LingoValue::Int(number) => {
+                u32::try_from(*number).map_err(|_| LingoError::UnknownMember {
+                    name: number.to_string(),
+                })
+            }
+            LingoValue::String(name) | LingoValue::Symbol(name) => {
+                (movie.member_number)(name, None)
+                    .ok_or_else(|| LingoError::UnknownMember { name: name.clone() })
+            }
+            other => Err(LingoError::UnknownMember {
+                name: other.to_text(),
+            }),
+        }
+    }
+
+    /// `member(member, cast)`, the cast is a name or a number
+    fn member_in_cast(
+        &self,
+        movie: &LingoMovie,
+        value: &LingoValue,
+        cast: &LingoValue,
+    ) -> Result<u32, LingoError> {
+        let unknown_member = || LingoError::UnknownMember {
+            name: format!("{} of cast {}", value.to_text(), cast.to_text()),
+        };
+        match (value, cast) {
+            (LingoValue::String(name) | LingoValue::Symbol(name), LingoValue::String(cast)) => {
+                (movie.member_number)(name, Some(cast)).ok_or_else(unknown_member)
+            }
+            (LingoValue::String(_) | LingoValue::Symbol(_), cast) => {
+                // a name found in another cast than the one asked for does not count
+                let number = self.member(movie, value)?;
+                let cast_lib = u32::try_from(cast.as_int()).map_err(|_| unknown_member())?;
+                if (number >> 16).max(1) == cast_lib.max(1) {
+                    Ok(number)
+                } else {
+                    Err(unknown_member())
+                }
+            }
+            (_, cast) => {
+                let cast_lib = u32::try_from(cast.as_int()).map_err(|_| unknown_member())?;
+                let member = self.member(movie, value)? & 0xFFFF;
+                Ok(if cast_lib <= 1 {
+                    member
+                } else {
+                    cast_lib << 16 | member
+                })
+            }
+        }
+    }
+
+    fn sprite(&mut self, channel: u16) -> &mut LingoSprite {
+        self.effects.push(LingoEffect::SpriteChanged(channel));
+        self.sprites.entry(channel).or_default()
+    }
+
+    fn builtin(
+        &mut self,
+        movie: &LingoMovie,
+        name: &str,
+        arguments: Vec<LingoValue>,
+        depth: usize,
+    ) -> Result<LingoValue, LingoError> {
+        let argument = |index: usize| arguments.get(index).cloned().unwrap_or_default();
+        let lowercase_name = name.to_lowercase();
+        let value = match lowercase_name.as_str() {
+            "sprite" => LingoValue::Sprite(argument(0).as_int() as u16),
+            "member" => LingoValue::Member(match arguments.get(1) {
+                Some(cast) => self.member_in_cast(movie, &argument(0), cast)?,
+                None => self.member(movie, &argument(0))?,
+            }),
+            // the score is not played, the rooms are built by hand
+            "go" => {
+                return Err(LingoError::Unsupported {
+                    handler: name.to_owned(),
+                    what: format!("go to frame {}", argument(0).to_text()),
+                })
+            }
+            "puppetsprite" => {
+                self.sprite(argument(0).as_int() as u16).puppet = argument(1).is_truthy();
+                LingoValue::Void
+            }
+            "puppetsound" => {
+                let (channel, member) = if arguments.len() > 1 {
+                    (argument(0).as_int() as u16, argument(1))
+                } else {
+                    (1, argument(0))
+                };
+                self.play_sound(movie, channel, &member)?;
+                LingoValue::Void
+            }
+            "sound" => match argument(0).to_text().to_lowercase().as_str() {
+                "stop" | "close" => {
+                    let channel = argument(1).as_int() as u16;
+                    self.effects.push(LingoEffect::StopSound { channel });
+                    LingoValue::Void
+                }
+                other => {
+                    return Err(LingoError::Unsupported {
+                        handler: name.to_owned(),
+                        what: format!("sound {other}"),
+                    })
+                }
+            },
+            // the stage is redrawn every frame anyway
+            "updatestage" | "cursor" | "puppettempo" | "puppetpalette" | "puppettransition" => {
+                LingoValue::Void
+            }
+            "point" => LingoValue::Point(argument(0).as_int(), argument(1).as_int()),
+            "list" => LingoValue::list(arguments),
+            "timeout" => LingoValue::Timeout(argument(0).to_text()),
+            "script" => {
+                let member = self.member(movie, &argument(0))?;
+                // the low half of the cast id is the member, the high half its cast library
+                movie
+                    .context
+                    .scripts
+                    .iter()
+                    .find(|(_, script)| script.cast_member as u32 & 0xFFFF == member)
+                    .map(|(number, _)| LingoValue::Script(*number))
+                    .ok_or_else(|| LingoError::UnknownMember {
+                        name: argument(0).to_text(),
+                    })?
+            }
+            "new" => {
+                let LingoValue::Script(number) = argument(0) else {
+                    return Err(LingoError::Unsupported {
+                        handler: name.to_owned(),
+                        what: format!("new({})", argument(0).to_text()),
+                    });
+                };
+                match self.object_call(movie, "new", arguments.clone(), depth) {
+                    Err(LingoError::Unsupported { .. }) => LingoValue::Script(number), // scripts without a new handler
+                    result => result?,
+                }
+            }
+            "string" => LingoValue::String(argument(0).to_text()),
+            "integer" => LingoValue::Int(argument(0).as_float().round() as i32),
+            "float" => LingoValue::Float(argument(0).as_float()),
+            "symbol" => LingoValue::Symbol(argument(0).to_text()),
+            "abs" => match argument(0) {
+                LingoValue::Float(float) => LingoValue::Float(float.abs()),
+                other => LingoValue::Int(other.as_int().wrapping_abs()),
+            },
+            "random" => LingoValue::Int(self.random(argument(0).as_int())),
+            "voidp" => LingoValue::from(matches!(argument(0), LingoValue::Void)),
+            "listp" => LingoValue::from(matches!(
+                argument(0),
+                LingoValue::List(_) | LingoValue::PropList(_)
+            )),
+            _ => list_method(&lowercase_name, &arguments)?.ok_or_else(|| {
+                LingoError::UnknownHandler {
+                    name: name.to_owned(),
+                }
+            })?,
+        };
+        Ok(value)
+    }
+
+    fn object_call(
+        &mut self,
+        movie: &LingoMovie,
+        method: &str,
+        arguments: Vec<LingoValue>,
+        depth: usize,
+    ) -> Result<LingoValue, LingoError> {
+        let unsupported = || LingoError::Unsupported {
+            handler: method.to_owned(),
+            what: format!(
+                "{:?}.{method}",
+                arguments.first().cloned().unwrap_or_default()
+            ),
+        };
+        match arguments.first() {
+            Some(LingoValue::Script(number)) => {
+                let script = movie.context.scripts.get(number).ok_or_else(unsupported)?;
+                let handler = script
+                    .handlers
+                    .iter()
+                    .find(|handler| handler.name.eq_ignore_ascii_case(method))
+                    .ok_or_else(unsupported)?;
+                self.run(
+                    movie,
+                    *number,
+                    script,
+                    handler,
+                    arguments.clone(),
+                    depth + 1,
+                )
+            }
+            Some(LingoValue::Timeout(name)) => {
+                let name = name.clone();
+                match method.to_lowercase().as_str() {
+                    "new" => {
+                        let argument =
+                            |index: usize| arguments.get(index).cloned().unwrap_or_default();
+                        self.timeouts.retain(|timeout| timeout.name != name);
+                        self.timeouts.push(LingoTimeout {
+                            name: name.clone(),
+                            dir: movie.dir.to_owned(),
+                            period: argument(1).as_int().max(0) as u64,
+                            handler: argument(2).to_text(),
+                            target: argument(3),
+                            elapsed: 0,
+                        });
+                        Ok(LingoValue::Timeout(name))
+                    }
+                    "forget" => {
+                        self.timeouts.retain(|timeout| timeout.name != name);
+                        Ok(LingoValue::Void)
+                    }
+                    _ => Err(unsupported()),
+                }
+            }
+            Some(LingoValue::List(_) | LingoValue::PropList(_)) => {
+                list_method(&method.to_lowercase(), &arguments)?.ok_or_else(unsupported)
+            }
+            _ => Err(unsupported()),
+        }
+    }
+
+    fn get_object_property(
+        &self,
+        object: &LingoValue,
+        property: &str,
+    ) -> Result<LingoValue, LingoError> {
+        let property_lowercase = property.to_lowercase();
+        let value = match (object, property_lowercase.as_str()) {
+            (LingoValue::Sprite(channel), _) => {
+                let sprite = self.sprites.get(channel).cloned().unwrap_or_default();
+                match property_lowercase.as_str() {
+                    "member" => sprite.member.map_or(LingoValue::Void, LingoValue::Member),
+                    "loch" => LingoValue::Int(sprite.loc_h),
+                    "locv" => LingoValue::Int(sprite.loc_v),
+                    "loc" => LingoValue::Point(sprite.loc_h, sprite.loc_v),
+                    "visible" => LingoValue::from(sprite.visible),
+                    "puppet" => LingoValue::from(sprite.puppet),
+                    _ => return Err(unsupported_property(object, property)),
+                }
+            }
+            (LingoValue::Member(number), "number") => LingoValue::Int(*number as i32),
+            (LingoValue::Point(h, _), "loch") => LingoValue::Int(*h),
+            (LingoValue::Point(_, v), "locv") => LingoValue::Int(*v),
+            (LingoValue::List(list), "count") => LingoValue::Int(lock(list).len() as i32),
+            (LingoValue::PropList(list), "count") => LingoValue::Int(lock(list).len() as i32),
+            (LingoValue::PropList(list), _) => lock(list)
+                .iter()
+                .find(|(key, _)| key.to_text().eq_ignore_ascii_case(property))
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default(),
+            (LingoValue::Timeout(name), "name") => LingoValue::String(name.clone()),
+            _ => return Err(unsupported_property(object, property)),
+        };
+        Ok(value)
+    }
+
+    fn set_object_property(
+        &mut self,
+        movie: &LingoMovie,
+        object: &LingoValue,
+        property: &str,
+        value: LingoValue,
+    ) -> Result<(), LingoError> {
+        match object {
+            LingoValue::Sprite(channel) => {
+                let member = match property.to_lowercase().as_str() {
+                    "member" | "castnum" => Some(self.member(movie, &value)?),
+                    _ => None,
+                };
+                let sprite = self.sprite(*channel);
+                match property.to_lowercase().as_str() {
+                    "member" | "castnum" => sprite.member = member,
+                    "loch" => sprite.loc_h = value.as_int(),
+                    "locv" => sprite.loc_v = value.as_int(),
+                    "loc" => {
+                        if let LingoValue::Point(h, v) = value {
+                            (sprite.loc_h, sprite.loc_v) = (h, v);
+                        }
+                    }
+                    "visible" => sprite.visible = value.is_truthy(),
+                    _ => return Err(unsupported_property(object, property)),
+                }
+            }
+            LingoValue::PropList(list) => {
+                set_property(
+                    &mut lock(list),
+                    LingoValue::Symbol(property.to_owned()),
+                    value,
+                );
+            }
+            _ => return Err(unsupported_property(object, property)),
+        }
+        Ok(())
+    }
+
+    fn play_sound(
+        &mut self,
+        movie: &LingoMovie,
+        channel: u16,
+        member: &LingoValue,
+    ) -> Result<(), LingoError> {
+        if !member.is_truthy() && !matches!(member, LingoValue::String(name) if !name.is_empty()) {
+            // puppetSound 0 stops the sound
+            self.effects.push(LingoEffect::StopSound { channel });
+            return Ok(());
+        }
+        let member = self.member(movie, member)?;
+        self.effects
+            .push(LingoEffect::PlaySound { channel, member });
+        Ok(())
+    }
+
+    /// A number from 1 to `maximum`, like lingo's random
+    fn random(&mut self, maximum: i32) -> i32 {
+        // xorshift, the seed only has to be something other than 0
+        let mut state = if self.random_state == 0 {
+            0x2545_F491
+        } else {
+            self.random_state
+        };
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        self.random_state = state;
+        (state % maximum.max(1) as u32) as i32 + 1
+    }
+}
+
+/// The handler called `name`, the script `preferred` is searched first
+fn find_handler<'a>(
+    context: &'a LingoContext,
+    name: &str,
+    preferred: Option<u32>,
+) -> Option<(u32, &'a LingoScript, &'a LingoHandler)> {
+    let in_script = |number: u32| {
+        let script = context.scripts.get(&number)?;
+        script
+            .handlers
+            .iter()
+            .find(|handler| handler.name.eq_ignore_ascii_case(name))
+            .map(|handler| (number, script, handler))
+    };
+    if let Some(found) = preferred.and_then(in_script) {
+        return Some(found);
+    }
+    let mut numbers: Vec<u32> = context.scripts.keys().copied().collect();
+    numbers.sort_unstable();
+    numbers.into_iter().find_map(in_script)
+}
+
+/// The list functions, they can be called as `count(list)` or `list.count()`,
+/// `None` when there is no such function for the value
+fn list_method(name: &str, arguments: &[LingoValue]) -> Result<Option<LingoValue>, LingoError> {
+    let argument = |index: usize| arguments.get(index).cloned().unwrap_or_default();
+    let position = |index: usize| argument(index).as_int().saturating_sub(1).max(0) as usize;
+    let out_of_range = |index: usize| LingoError::OutOfRange {
+        handler: name.to_owned(),
+        index: index + 1,
+    };
+    let Some(first) = arguments.first() else {
+        return Ok(None);
+    };
+
+    let value = match (first, name) {
+        (LingoValue::List(list), "count") => LingoValue::Int(lock(list).len() as i32),
+        (LingoValue::List(list), "getat") => lock(list)
+            .get(position(1))
+            .cloned()
+            .ok_or_else(|| out_of_range(position(1)))?,
+        (LingoValue::List(list), "getlast") => lock(list).last().cloned().unwrap_or_default(),
+        (LingoValue::List(list), "setat") => {
+            let mut list = lock(list);
+            let index = position(1);
+            if index >= MAX_LIST_LENGTH {
+                return Err(out_of_range(index));
+            }
+            if list.len() <= index {
+                list.resize(index + 1, LingoValue::Void);
+            }
+            list[index] = argument(2);
+            LingoValue::Void
+        }
+        (LingoValue::List(list), "append" | "add") => {
+            lock(list).push(argument(1));
+            LingoValue::Void
+        }
+        (LingoValue::List(list), "deleteat") => {
+            let mut list = lock(list);
+            if position(1) < list.len() {
+                list.remove(position(1));
+            }
+            LingoValue::Void
+        }
+        (LingoValue::List(list), "getpos" | "getone") => {
+            let wanted = argument(1);
+            let found = lock(list).iter().position(|value| value.lingo_eq(&wanted));
+            LingoValue::Int(found.map_or(0, |index| index as i32 + 1))
+        }
+        (LingoValue::PropList(list), "count") => LingoValue::Int(lock(list).len() as i32),
+        (LingoValue::PropList(list), "getat") => lock(list)
+            .get(position(1))
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| out_of_range(position(1)))?,
+        (LingoValue::PropList(list), "getprop" | "getaprop") => {
+            let wanted = argument(1);
+            lock(list)
+                .iter()
+                .find(|(key, _)| key.lingo_eq(&wanted))
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        }
+        (LingoValue::PropList(list), "addprop") => {
+            lock(list).push((argument(1), argument(2)));
+            LingoValue::Void
+        }
+        (LingoValue::PropList(list), "setprop" | "setaprop") => {
+            set_property(&mut lock(list), argument(1), argument(2));
+            LingoValue::Void
+        }
+        (LingoValue::PropList(list), "deleteprop") => {
+            let wanted = argument(1);
+            lock(list).retain(|(key, _)| !key.lingo_eq(&wanted));
+            LingoValue::Void
+        }
+        (LingoValue::String(text), "count") => LingoValue::Int(text.chars().count() as i32),
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+fn set_property(list: &mut Vec<(LingoValue, LingoValue)>, key: LingoValue, value: LingoValue) {
+    match list
+        .iter_mut()
+        .find(|(existing, _)| existing.lingo_eq(&key))
+    {
+        Some((_, existing)) => *existing = value,
+        None => list.push((key, value)),
+    }
+}
+
+fn unsupported_property(object: &LingoValue, property: &str) -> LingoError {
+    LingoError::Unsupported {
+        handler: String::new(),
+        what: format!("{object:?}.{property}"),
+    }
+}
+
+// a handler that panicked while holding a list can not leave it half changed, so the lock can be taken anyway
+fn lock<T>(shared: &Mutex<T>) -> MutexGuard<'_, T> {
+    shared
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn arithmetic(opcode: LingoOpcode, left: &LingoValue, right: &LingoValue) -> LingoValue {
+    if let (LingoValue::Point(left_h, left_v), LingoValue::Point(right_h, right_v)) = (left, right)
+    {
+        return match opcode {
+            LingoOpcode::Add => LingoValue::Point(left_h + right_h, left_v + right_v),
+            LingoOpcode::Subtract => LingoValue::Point(left_h - right_h, left_v - right_v),
+            _ => LingoValue::Void,
+        };
+    }
+    if matches!(left, LingoValue::Float(_)) || matches!(right, LingoValue::Float(_)) {
+        let (left, right) = (left.as_float(), right.as_float());
+        return LingoValue::Float(match opcode {
+            LingoOpcode::Multiply => left * right,
+            LingoOpcode::Add => left + right,
+            LingoOpcode::Subtract => left - right,
+            LingoOpcode::Divide => left / right,
+            _ => left % right,
+        });
+    }
+    let (left, right) = (left.as_int(), right.as_int());
+    LingoValue::Int(match opcode {
+        LingoOpcode::Multiply => left.wrapping_mul(right),
+        LingoOpcode::Add => left.wrapping_add(right),
+        LingoOpcode::Subtract => left.wrapping_sub(right),
+        LingoOpcode::Divide => left.checked_div(right).unwrap_or_default(),
+        _ => left.checked_rem(right).unwrap_or_default(),
+    })
+}
+
+impl From<bool> for LingoValue {
+    fn from(value: bool) -> Self {
+        Self::Int(i32::from(value))
+    }
+}
+
+impl LingoValue {
+    pub fn list(values: Vec<Self>) -> Self {
+        Self::List(Arc::new(Mutex::new(values)))
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Void => false,
+            Self::Int(int) => *int != 0,
+            Self::Float(float) => *float != 0.,
+            _ => true,
+        }
+    }
+
+    pub fn as_int(&self) -> i32 {
+        match self {
+            Self::Int(int) => *int,
+            Self::Float(float) => *float as i32,
+            Self::String(text) => text.trim().parse().unwrap_or_default(),
+            Self::Member(number) => *number as i32,
+            Self::Sprite(channel) => i32::from(*channel),
+            _ => 0,
+        }
+    }
+
+    pub fn as_float(&self) -> f64 {
+        match self {
+            Self::Float(float) => *float,
+            Self::String(text) => text.trim().parse().unwrap_or_default(),
+            other => f64::from(other.as_int()),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        match self {
+            Self::Void => String::new(),
+            Self::Int(int) => int.to_string(),
+            Self::Float(float) => format!("{float:.4}"),
+            Self::String(text) | Self::Symbol(text) | Self::Timeout(text) => text.clone(),
+            Self::Point(h, v) => format!("point({h}, {v})"),
+            Self::List(list) => {
+                let values: Vec<String> = lock(list).iter().map(Self::to_text).collect();
+                format!("[{}]", values.join(", "))
+            }
+            Self::PropList(list) => {
+                let values: Vec<String> = lock(list)
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.to_text(), value.to_text()))
+                    .collect();
+                format!("[{}]", values.join(", "))
+            }
+            Self::ArgList { values, .. } => {
+                let values: Vec<String> = values.iter().map(Self::to_text).collect();
+                values.join(", ")
+            }
+            Self::Sprite(channel) => format!("sprite({channel})"),
+            Self::Member(number) => format!("member({number})"),
+            Self::Script(number) => format!("script({number})"),
+        }
+    }
+
+    /// Lingo compares strings and symbols without looking at the case
+    pub fn lingo_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::String(left) | Self::Symbol(left),
+                Self::String(right) | Self::Symbol(right),
+            ) => left.eq_ignore_ascii_case(right),
+            (Self::Void, Self::Void) => true,
+            (Self::Sprite(left), Self::Sprite(right)) => left == right,
+            (Self::Member(left), Self::Member(right))
+            | (Self::Script(left), Self::Script(right)) => left == right,
+            (Self::Point(left_h, left_v), Self::Point(right_h, right_v)) => {
+                left_h == right_h && left_v == right_v
+            }
+            (Self::List(left), Self::List(right)) => Arc::ptr_eq(left, right),
+            (Self::PropList(left), Self::PropList(right)) => Arc::ptr_eq(left, right),
+            (Self::Float(_), _) | (_, Self::Float(_)) => self.as_float() == other.as_float(),
+            _ => self.as_int() == other.as_int() && !matches!(self, Self::Void),
+        }
+    }
+
+    fn into_arguments(self) -> Vec<Self> {
+        match self {
+            Self::ArgList { values, .. } => values,
+            other => vec![other],
+        }
+    }
+
+    fn into_call(self) -> (Vec<Self>, bool) {
+        match self {
+            Self::ArgList { values, returns } => (values, returns),
+            other => (vec![other], true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use LingoOpcode::*;
+
+    // instructions are one position apart, so jumps count instructions
+    fn handler(
+        name: &str,
+        arguments: &[&str],
+        locals: usize,
+        code: &[(LingoOpcode, i32)],
+    ) -> LingoHandler {
+        LingoHandler {
+            name: name.to_owned(),
+            arguments: arguments
+                .iter()
+                .map(|&argument| argument.to_owned())
+                .collect(),
+            locals: (0..locals).map(|local| format!("local{local}")).collect(),
+            globals: Vec::new(),
+            instructions: code
+                .iter()
+                .zip(0..)
+                .map(|(&(opcode, operand), position)| LingoInstruction {
+                    position,
+                    opcode,
+                    operand,
+                })
+                .collect(),
+        }
+    }
+
+    fn context(names: &[&str], handlers: Vec<LingoHandler>) -> LingoContext {
+        let script = LingoScript {
+            cast_member: 1,
+            flags: 0,
+            properties: Vec::new(),
+            globals: Vec::new(),
+            handlers,
+            literals: Vec::new(),
+        };
+        // LctX numbers variables one apart
+        LingoContext::new(
+            names.iter().map(|&name| name.to_owned()).collect(),
+            HashMap::from([(1, script)]),
+            true,
+            600,
+        )
+    }
+
+    fn call(
+        context: &LingoContext,
+        handler: &str,
+        arguments: Vec<LingoValue>,
+    ) -> Result<LingoValue, LingoError> {
+        let member_number = |name: &str, _: Option<&str>| (name == "bird").then_some(7);
+        let movie = LingoMovie {
+            dir: "test",
+            context,
+            member_number: &member_number,
+        };
+        LingoRuntime::default().call(&movie, handler, arguments)
+    }
+
+    fn run(names: &[&str], code: &[(LingoOpcode, i32)]) -> Result<LingoValue, LingoError> {
+        call(
+            &context(names, vec![handler("test", &[], 2, code)]),
+            "test",
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn stack_operations() {
+        let code = [
+            (PushInt8, 2),
+            (PushInt8, 3),
+            (Add, 0),
+            (PushInt8, 4),
+            (Swap, 0),
+            (Subtract, 0),
+            (Return, 0),
+        ];
+        assert_eq!(run(&[], &code).unwrap().as_int(), -1);
+        let code = [
+            (PushInt8, 6),
+            (Peek, 0),
+            (Multiply, 0),
+            (PushInt8, 9),
+            (Pop, 1),
+            (Return, 0),
+        ];
+        assert_eq!(run(&[], &code).unwrap().as_int(), 36);
+        assert!(matches!(
+            run(&[], &[(PushInt8, 1), (Add, 0)]),
+            Err(LingoError::StackUnderflow { .. })
+        ));
+    }
+
+    #[test]
+    fn jumps() {
+        // repeat while counter < 5, counter = counter + 1
+        let code = [
+            (PushZero, 0),
+            (SetLocal, 0),
+            (GetLocal, 0),
+            (PushInt8, 5),
+            (LessThan, 0),
+            (JumpIfZero, 6),
+            (GetLocal, 0),
+            (PushInt8, 1),
+            (Add, 0),
+            (SetLocal, 0),
+            (EndRepeat, 8),
+            (GetLocal, 0),
+            (Return, 0),
+        ];
+        assert_eq!(run(&[], &code).unwrap().as_int(), 5);
+        let code = [(Jump, 2), (PushInt8, 1), (PushInt8, 2), (Return, 0)];
+        assert_eq!(run(&[], &code).unwrap().as_int(), 2);
+    }
+
+    #[test]
+    fn calls_and_returns() {
+        let double = handler(
+            "double",
+            &["value"],
+            0,
+            &[(GetParameter, 0), (PushInt8, 2), (Multiply, 0), (Return, 0)],
+        );
+        let main = handler(
+            "main",
+            &[],
+            0,
+            &[
+                (PushInt8, 5),
+                (PushArgListNoReturn, 1),
+                (LocalCall, 0), // the result is dropped
+                (PushInt8, 10),
+                (PushArgList, 1),
+                (LocalCall, 0),
+                (PushArgList, 1),
+                (ExternalCall, 0),
+                (Return, 0),
+            ],
+        );
+        let context = context(&["double"], vec![double, main]);
+        assert_eq!(call(&context, "main", Vec::new()).unwrap().as_int(), 40);
+        assert_eq!(
+            call(&context, "Double", vec![LingoValue::Int(4)])
+                .unwrap()
+                .as_int(),
+            8
+        );
+        assert!(matches!(
+            call(&context, "missing", Vec::new()),
+            Err(LingoError::UnknownHandler { .. })
+        ));
+    }
+
+    #[test]
+    fn recursion_is_limited() {
+        let code = [(PushArgList, 0), (LocalCall, 0), (Return, 0)];
+        assert!(matches!(run(&[], &code), Err(LingoError::TooDeep { .. })));
+    }
+
+    #[test]
+    fn instruction_budget() {
+        assert!(matches!(
+            run(&[], &[(Jump, 0)]),
+            Err(LingoError::TooLong { .. })
+        ));
+        // the budget is per call
+        let context = context(
+            &[],
+            vec![
+                handler("spin", &[], 0, &[(Jump, 0)]),
+                handler("quick", &[], 0, &[(PushInt8, 1), (Return, 0)]),
+            ],
+        );
+        let member_number = |_: &str, _: Option<&str>| None;
+        let movie = LingoMovie {
+            dir: "test",
+            context: &context,
+            member_number: &member_number,
+        };
+        let mut runtime = LingoRuntime::default();
+        assert!(runtime.call(&movie, "spin", Vec::new()).is_err());
+        assert_eq!(
+            runtime.call(&movie, "quick", Vec::new()).unwrap().as_int(),
+            1
+        );
+    }
+
+    #[test]
+    fn undeclared_variables() {
+        assert!(matches!(
+            run(&[], &[(PushInt8, 1), (SetLocal, 2)]),
+            Err(LingoError::BadVariable { index: 2, .. })
+        ));
+        let code = [
+            (PushInt8, 1),
+            (SetParameter, 1),
+            (GetParameter, 1),
+            (Return, 0),
+        ];
+        let declared = context(&[], vec![handler("test", &["a", "b"], 0, &code)]);
+        assert_eq!(call(&declared, "test", Vec::new()).unwrap().as_int(), 1);
+        let code = [(PushInt8, 1), (SetParameter, 2)];
+        let undeclared = context(&[], vec![handler("test", &["a", "b"], 0, &code)]);
+        assert!(matches!(
+            call(&undeclared, "test", Vec::new()),
+            Err(LingoError::BadVariable { index: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn list_methods() {
+        let list = LingoValue::list(vec![LingoValue::Int(1), LingoValue::Int(2)]);
+        let with = |values: &[LingoValue]| {
+            let mut arguments = vec![list.clone()];
+            arguments.extend_from_slice(values);
+            arguments
+        };
+        let method =
+            |name: &str, values: &[LingoValue]| list_method(name, &with(values)).unwrap().unwrap();
+
+        assert_eq!(method("count", &[]).as_int(), 2);
+        assert_eq!(method("getat", &[LingoValue::Int(2)]).as_int(), 2);
+        method("append", &[LingoValue::Int(3)]);
+        method("setat", &[LingoValue::Int(5), LingoValue::Int(5)]);
+        assert_eq!(list.to_text(), "[1, 2, 3, , 5]");
+        assert_eq!(method("getpos", &[LingoValue::Int(3)]).as_int(), 3);
+        method("deleteat", &[LingoValue::Int(1)]);
+        assert_eq!(list.to_text(), "[2, 3, , 5]");
+        assert_eq!(method("getlast", &[]).as_int(), 5);
+
+        // indexes below 1 are the first value, far past the end is an error instead of a huge list
+        assert_eq!(method("getat", &[LingoValue::Int(i32::MIN)]).as_int(), 2);
+        assert!(matches!(
+            list_method("getat", &with(&[LingoValue::Int(9)])),
+            Err(LingoError::OutOfRange { index: 9, .. })
+        ));
+        assert!(matches!(
+            list_method(
+                "setat",
+                &with(&[LingoValue::Int(i32::MAX), LingoValue::Void])
+            ),
+            Err(LingoError::OutOfRange { .. })
+        ));
+        assert_eq!(method("count", &[]).as_int(), 4);
+
+        let properties = LingoValue::PropList(Arc::new(Mutex::new(Vec::new())));
+        let arguments = |values: &[LingoValue]| {
+            let mut arguments = vec![properties.clone()];
+            arguments.extend_from_slice(values);
+            arguments
+        };
+        let symbol = |name: &str| LingoValue::Symbol(name.to_owned());
+        list_method(
+            "setprop",
+            &arguments(&[symbol("speed"), LingoValue::Int(3)]),
+        )
+        .unwrap();
+        list_method(
+            "setprop",
+            &arguments(&[symbol("Speed"), LingoValue::Int(4)]),
+        )
+        .unwrap();
+        let speed = list_method("getprop", &arguments(&[symbol("SPEED")]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(speed.as_int(), 4);
+        assert_eq!(properties.to_text(), "[speed: 4]");
+        assert!(list_method("nosuchmethod", &arguments(&[]))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn member_numbers() {
+        let member = |argument: (LingoOpcode, i32)| {
+            run(
+                &["member", "bird"],
+                &[argument, (PushArgList, 1), (ExternalCall, 0), (Return, 0)],
+            )
+        };
+        assert!(matches!(member((PushInt8, 12)), Ok(LingoValue::Member(12))));
+        assert!(matches!(member((PushSymbol, 1)), Ok(LingoValue::Member(7))));
+        assert!(matches!(
+            member((PushInt8, -1)),
+            Err(LingoError::UnknownMember { .. })
+        ));
+    }
+
+    #[test]
+    fn score_playback_is_unsupported() {
+        let code = [(PushInt8, 3), (PushArgListNoReturn, 1), (ExternalCall, 0)];
+        assert!(matches!(
+            run(&["go"], &code),
+            Err(LingoError::Unsupported { .. })
+        ));
+        let code = [
+            (PushSymbol, 1),
+            (PushInt8, 1),
+            (PushArgListNoReturn, 2),
+            (ExternalCall, 0),
+        ];
+        assert!(matches!(
+            run(&["sound", "fadeIn"], &code),
+            Err(LingoError::Unsupported { .. })
+        ));
+    }
+}
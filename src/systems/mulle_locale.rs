@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+
+use crate::game_definition::{GameDefinition, MemberOverrides};
+
+// Every country got its own release of the games, with the same files but other texts and sometimes other members
+// The language is guessed from the text members once the files are loaded, by counting common words of every
+// language. `locale` in openmulle.toml or --locale picks one instead
+// The member numbers in rooms.json and the screens are the ones of the original swedish release, the game definition
+// can map them to other members per language
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    Swedish,
+    Norwegian,
+    Danish,
+    Finnish,
+    Dutch,
+    German,
+    English,
+}
+
+// short words that come up in almost any text of the language, and preferably in no other
+const COMMON_WORDS: &[(Language, &[&str])] = &[
+    (
+        Language::Swedish,
+        &["och", "är", "inte", "jag", "vad", "det", "att", "ska"],
+    ),
+    (
+        Language::Norwegian,
+        &["og", "ikke", "jeg", "hva", "meg", "av", "litt", "skal"],
+    ),
+    (
+        Language::Danish,
+        &["og", "ikke", "jeg", "hvad", "mig", "af", "lidt", "skal"],
+    ),
+    (
+        Language::Finnish,
+        &["ja", "on", "ei", "että", "minä", "sinä", "mitä", "se"],
+    ),
+    (
+        Language::Dutch,
+        &["het", "een", "niet", "ik", "wat", "van", "je", "en"],
+    ),
+    (
+        Language::German,
+        &["und", "ist", "nicht", "ich", "das", "was", "ein", "der"],
+    ),
+    (
+        Language::English,
+        &["the", "and", "is", "not", "you", "what", "it", "of"],
+    ),
+];
+
+// this many more common words than any other language and the others can not catch up anymore,
+// the languages that share words (norwegian and danish) only pull apart on the words they do not share
+const DECISIVE_LEAD: usize = 20;
+
+fn clear_lead(counts: &[usize]) -> bool {
+    let mut sorted = counts.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    match sorted.as_slice() {
+        [best, second, ..] => *best >= second + DECISIVE_LEAD,
+        _ => false,
+    }
+}
+
+impl Language {
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::Swedish => "sv",
+            Self::Norwegian => "no",
+            Self::Danish => "da",
+            Self::Finnish => "fi",
+            Self::Dutch => "nl",
+            Self::German => "de",
+            Self::English => "en",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        COMMON_WORDS
+            .iter()
+            .map(|(language, _)| *language)
+            .find(|language| language.code().eq_ignore_ascii_case(code))
+    }
+
+    /// The language most of the words are in, `None` when there is no text with common words at all
+    /// reading stops once one language is far enough ahead, so the texts are only decoded as far as needed
+    pub fn detect<'a>(texts: impl Iterator<Item = &'a str>) -> Option<Self> {
+        let mut counts = [0; COMMON_WORDS.len()];
+        for text in texts {
+            for word in text
+                .split(|c: char| !c.is_alphabetic())
+                .filter(|word| !word.is_empty())
+            {
+                let word = word.to_lowercase();
+                for (count, (_, common)) in counts.iter_mut().zip(COMMON_WORDS) {
+                    if common.contains(&word.as_str()) {
+                        *count += 1;
+                    }
+                }
+            }
+            if clear_lead(&counts) {
+                break;
+            }
+        }
+        // on a tie the language that comes first wins, the original release is first
+        COMMON_WORDS
+            .iter()
+            .zip(counts)
+            .filter(|(_, count)| *count > 0)
+            .fold(None, |best, ((language, _), count)| match best {
+                Some((_, best_count)) if best_count >= count => best,
+                _ => Some((*language, count)),
+            })
+            .map(|(language, _)| language)
+    }
+}
+
+/// The release that was loaded
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Locale {
+    pub language: Language,
+    members: MemberOverrides,
+}
+
+impl Locale {
+    pub fn new(language: Language, game: &GameDefinition) -> Self {
+        Self {
+            language,
+            members: game
+                .locales
+                .get(language.code())
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The member of this release that stands in for `number` of the original release
+    pub fn member(&self, dir: &str, number: u32) -> u32 {
+        self.members
+            .get(dir)
+            .and_then(|members| members.get(&number))
+            .copied()
+            .unwrap_or(number)
+    }
+
+    /// The member of the original release that `member` turns into `number`, the reverse of `member`.
+    /// `None` when this release only shows `number` in place of another member
+    pub fn original_member(&self, dir: &str, number: u32) -> Option<u32> {
+        let Some(members) = self.members.get(dir) else {
+            return Some(number);
+        };
+        // several originals can be replaced by the same member, any of them will do
+        members
+            .iter()
+            .filter(|(_, replacement)| **replacement == number)
+            .map(|(original, _)| *original)
+            .min()
+            .or_else(|| (!members.contains_key(&number)).then_some(number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lead_needs_a_margin_over_the_runner_up() {
+        assert!(clear_lead(&[25, 5, 0]));
+        assert!(clear_lead(&[0, 20, 0]));
+        assert!(!clear_lead(&[24, 5, 0]));
+        assert!(!clear_lead(&[30, 30, 0]));
+        assert!(!clear_lead(&[40]));
+        assert!(!clear_lead(&[]));
+    }
+
+    #[test]
+    fn tells_norwegian_from_danish() {
+        let norwegian = ["jeg vet ikke hva det er", "gi meg litt av det"];
+        let danish = ["jeg ved ikke hvad det er", "giv mig lidt af det"];
+        assert_eq!(
+            Language::detect(norwegian.into_iter()),
+            Some(Language::Norwegian)
+        );
+        assert_eq!(Language::detect(danish.into_iter()), Some(Language::Danish));
+    }
+
+    #[test]
+    fn ties_go_to_the_earlier_language() {
+        // only words norwegian and danish share
+        assert_eq!(
+            Language::detect(["Og ikke jeg!"].into_iter()),
+            Some(Language::Norwegian)
+        );
+        assert_eq!(Language::detect(["12 34", ""].into_iter()), None);
+    }
+
+    #[test]
+    fn stops_reading_on_a_clear_lead() {
+        let swedish = "jag vet inte vad det är och ".repeat(4);
+        let english = "the and is not ".repeat(20);
+        let texts = [swedish.as_str(), english.as_str(), english.as_str()];
+        assert_eq!(Language::detect(texts.into_iter()), Some(Language::Swedish));
+    }
+
+    #[test]
+    fn members_fall_back_to_the_original_release() {
+        let game: GameDefinition = serde_json::from_str(
+            r#"{
+                "name": "test",
+                "files": [],
+                "start_state": "Room",
+                "locales": { "no": { "00.CXT": { "12": 40 } } }
+            }"#,
+        )
+        .unwrap();
+        let norwegian = Locale::new(Language::Norwegian, &game);
+        assert_eq!(norwegian.member("00.CXT", 12), 40);
+        assert_eq!(norwegian.member("00.CXT", 13), 13);
+        assert_eq!(norwegian.member("02.DXR", 12), 12);
+        assert_eq!(
+            Locale::new(Language::Danish, &game).member("00.CXT", 12),
+            12
+        );
+    }
+
+    #[test]
+    fn original_members_map_back() {
+        let game: GameDefinition = serde_json::from_str(
+            r#"{
+                "name": "test",
+                "files": [],
+                "start_state": "Room",
+                "locales": { "no": { "00.CXT": { "12": 40, "13": 12, "20": 21, "22": 21 } } }
+            }"#,
+        )
+        .unwrap();
+        let norwegian = Locale::new(Language::Norwegian, &game);
+        assert_eq!(norwegian.original_member("00.CXT", 40), Some(12));
+        // 12 itself is shown as 40, but 13 is shown as 12
+        assert_eq!(norwegian.original_member("00.CXT", 12), Some(13));
+        // 20 is shown as 21, which nothing is shown as
+        assert_eq!(norwegian.original_member("00.CXT", 20), None);
+        assert_eq!(norwegian.original_member("00.CXT", 21), Some(20));
+        assert_eq!(norwegian.original_member("00.CXT", 5), Some(5));
+        assert_eq!(norwegian.original_member("02.DXR", 12), Some(12));
+        for number in [40, 12, 21, 5] {
+            let original = norwegian.original_member("00.CXT", number).unwrap();
+            assert_eq!(norwegian.member("00.CXT", original), number);
+        }
+    }
+}
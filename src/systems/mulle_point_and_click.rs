@@ -1,8 +1,9 @@
 use std::borrow::BorrowMut;
 
 use crate::{
+    parsers::director::MacromediaCastBitmapMetadata,
     render::scaler::{OuterCamera, PIXEL_PERFECT_LAYERS},
-    screens::{trash_heap::TrashState, yard::RoomState},
+    screens::yard::RoomState,
     GameState,
 };
 use bevy::{
@@ -17,16 +18,14 @@ use bevy::{
     prelude::*,
     render::camera::Camera,
     transform::components::{GlobalTransform, Transform},
-    utils::hashbrown::hash_map::Values,
     window::{PrimaryWindow, Window},
 };
 use serde::{Deserialize, Serialize};
 
 use super::{
-    mulle_asset_helper::{
-        MacromediaCastBitmapMetadata, MulleAssetHelp, MulleAssetHelper, MulleImage,
-    },
-    mulle_car::{Car, CarEntity, PartDB, PartLocation},
+    mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper, MulleAssetState, MulleImage},
+    mulle_car::{Car, CarEntity, PartDB, PartLocation, CAR_MOVIE},
+    mulle_lingo::LingoCall,
 };
 
 pub struct MullePointandClickPlugin;
@@ -35,7 +34,12 @@ impl Plugin for MullePointandClickPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<MyWorldCoords>()
             .add_systems(Update, my_cursor_system)
-            .add_systems(Update, mouse_click_system)
+            .add_systems(
+                Update,
+                mouse_click_system
+                    .run_if(in_state(MulleAssetState::Loaded))
+                    .run_if(not(resource_exists::<ClicksPaused>)),
+            )
             .add_systems(Update, update_clickables);
     }
 }
@@ -49,6 +53,10 @@ pub struct MulleClickable {
     click: Vec<ClickAction>,
 }
 
+/// While this resource exists clicks do not reach the clickables, so a panel on top of the game can be used
+#[derive(Resource)]
+pub struct ClicksPaused;
+
 #[derive(Serialize, Deserialize)]
 pub struct MulleClickableSerializable {
     pub sprite_default_asset_dir: String,
@@ -93,14 +101,12 @@ pub fn mulle_clickable_from_name(
     _dir_hover: &str,
     name_hover: u32,
     mulle_asset_helper: &bevy::prelude::Res<'_, MulleAssetHelp>,
-) -> MulleClickable {
+) -> Option<MulleClickable> {
     let meta_default = mulle_asset_helper
-        .get_mulle_image_by_asset_number(dir_default.to_string(), name_default)
-        .unwrap();
-    let meta_hover = mulle_asset_helper
-        .get_mulle_image_by_asset_number(dir_default.to_string(), name_hover)
-        .unwrap();
-    MulleClickable {
+        .get_mulle_image_by_asset_number(dir_default.to_string(), name_default)?;
+    let meta_hover =
+        mulle_asset_helper.get_mulle_image_by_asset_number(dir_default.to_string(), name_hover)?;
+    Some(MulleClickable {
         sprite_default: meta_default.clone(),
         sprite_hover: meta_hover.clone(),
         click,
@@ -120,7 +126,7 @@ pub fn mulle_clickable_from_name(
                 - i32::from(meta_hover.bitmap_metadata.image_width)) as f32,
             f32::from(meta_hover.bitmap_metadata.image_reg_y),
         ),
-    }
+    })
 }
 
 pub fn deploy_clickables<'a, T: Component + Clone, L>(
@@ -148,9 +154,20 @@ pub fn deploy_clickables<'a, T: Component + Clone, L>(
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ClickAction {
-    GamestateTransition { goal_state: GameState },
-    RoomstateTransition { goal_state: String },
-    PlayCutscene { cutscene_name: String },
+    GamestateTransition {
+        goal_state: GameState,
+    },
+    RoomstateTransition {
+        goal_state: String,
+    },
+    PlayCutscene {
+        cutscene_name: String,
+    },
+    /// Runs a handler from the scripts of a movie
+    RunLingo {
+        asset_dir: String,
+        handler: String,
+    },
 }
 #[derive(Component)]
 struct Hovered;
@@ -171,6 +188,7 @@ fn image_metadata_to_rect(image: &MacromediaCastBitmapMetadata, part: &PartDB) -
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn update_clickables(
     mut query: Query<
         (&mut Sprite, &MulleClickable, &mut Transform),
@@ -217,7 +235,7 @@ fn update_clickables(
                             continue;
                         }
                         let image = mulle_asset_helper
-                            .get_mulle_image_by_name("cddata.cxt".to_owned(), use_view.to_string())
+                            .get_mulle_image_by_name(CAR_MOVIE.to_owned(), use_view.to_string())
                             .unwrap();
                         let snap_point = image_metadata_to_rect(&image.bitmap_metadata, morph);
                         if mycoords.0.distance(snap_point) < 25.
@@ -358,7 +376,7 @@ fn create_morph_variant(
             continue;
         }
         let image = mulle_asset_helper
-            .get_mulle_image_by_name("cddata.cxt".to_owned(), use_view.to_string())
+            .get_mulle_image_by_name(CAR_MOVIE.to_owned(), use_view.to_string())
             .unwrap();
         let snap_point = {
             let rect = Rect::new(
@@ -459,6 +477,7 @@ fn my_cursor_system(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn mouse_click_system(
     mut mouse_button_input_events: EventReader<MouseButtonInput>,
     mycoords: ResMut<MyWorldCoords>,
@@ -469,6 +488,7 @@ fn mouse_click_system(
     mut room_state: ResMut<RoomState>,
     mut car: ResMut<Car>,
     mulle_asset_helper: Res<MulleAssetHelp>,
+    mut lingo_calls: EventWriter<LingoCall>,
 ) {
     let world_position = mycoords.0;
     for event in mouse_button_input_events.read() {
@@ -481,6 +501,13 @@ fn mouse_click_system(
                                 game_state.set(goal_state.to_owned());
                             }
                             ClickAction::PlayCutscene { cutscene_name: _ } => {}
+                            ClickAction::RunLingo { asset_dir, handler } => {
+                                lingo_calls.send(LingoCall {
+                                    dir: asset_dir.to_owned(),
+                                    handler: handler.to_owned(),
+                                    arguments: Vec::new(),
+                                });
+                            }
                             ClickAction::RoomstateTransition { goal_state } => {
                                 room_state.current_room = goal_state.to_string();
                                 room_state.set_changed();
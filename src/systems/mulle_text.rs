@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    launch::LaunchSettings,
+    parsers::director::text::MacromediaTextRun,
+    render::scaler::PIXEL_PERFECT_LAYERS,
+    systems::mulle_asset_helper::{MulleAssetHelp, MulleAssetHelper},
+};
+
+// Text members are drawn with the bevy text pipeline, the fonts of the game are not shipped with it
+// a font is used when assets/fonts has a .ttf named after the font map of the movie, otherwise the default font
+// Bold and italic runs look for "<name> Bold.ttf" and friends first, underlines are not drawn
+
+const FONTS_DIR: &str = "fonts";
+
+pub struct MulleTextPlugin;
+
+impl Plugin for MulleTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, show_texts);
+    }
+}
+
+/// Shows a text member, the transform of this entity is where the top left corner of the text ends up
+#[derive(Component)]
+#[require(Transform, Visibility)]
+pub struct MulleTextBox {
+    dir: String,
+    member: u32,
+}
+
+impl MulleTextBox {
+    pub const fn new(dir: String, member: u32) -> Self {
+        Self { dir, member }
+    }
+}
+
+fn show_texts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mulle_asset_helper: Res<MulleAssetHelp>,
+    settings: Res<LaunchSettings>,
+    text_boxes: Query<(Entity, &MulleTextBox), Added<MulleTextBox>>,
+) {
+    for (entity, text_box) in &text_boxes {
+        let Some(text) = mulle_asset_helper
+            .get_mulle_text_by_asset_number(text_box.dir.clone(), text_box.member)
+        else {
+            eprintln!("text {} {} not found", text_box.dir, text_box.member);
+            continue;
+        };
+
+        commands.entity(entity).with_children(|parent| {
+            parent
+                .spawn((Text2d::default(), Anchor::TopLeft, PIXEL_PERFECT_LAYERS))
+                .with_children(|text_root| {
+                    for (span, run) in text.spans() {
+                        let [r, g, b] = run.color;
+                        text_root.spawn((
+                            TextSpan::new(span),
+                            TextFont {
+                                font: font(
+                                    &asset_server,
+                                    &mulle_asset_helper,
+                                    &settings.assets_dir,
+                                    &text_box.dir,
+                                    run,
+                                ),
+                                font_size: f32::from(run.size),
+                                ..default()
+                            },
+                            TextColor(Color::srgb_u8(r, g, b)),
+                        ));
+                    }
+                });
+        });
+    }
+}
+
+fn font(
+    asset_server: &AssetServer,
+    mulle_asset_helper: &MulleAssetHelp,
+    assets_dir: &Path,
+    dir: &str,
+    run: &MacromediaTextRun,
+) -> Handle<Font> {
+    let Some(name) = mulle_asset_helper.get_font_name(dir.to_owned(), run.font_id) else {
+        return Handle::default();
+    };
+    let style = match (run.bold, run.italic) {
+        (true, true) => " Bold Italic",
+        (true, false) => " Bold",
+        (false, true) => " Italic",
+        (false, false) => "",
+    };
+    [
+        format!("{FONTS_DIR}/{name}{style}.ttf"),
+        format!("{FONTS_DIR}/{name}.ttf"),
+    ]
+    .into_iter()
+    .find(|path| assets_dir.join(path).exists())
+    .map_or_else(Handle::default, |path| asset_server.load(path))
+}